@@ -0,0 +1,183 @@
+use crate::mahjong::strategy::block_strategy::{Chow, Meld, Pung};
+use crate::mahjong::tile::{hand_counts::HandCounts, mahjong_tile::MahjongTile, tile_index::TileIndex};
+
+// Every way a complete hand can be read. Hands like 111222333m have more than
+// one reading, and scoring picks whichever is worth the most.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decomposition {
+    Standard {
+        pair: MahjongTile,
+        // Sets formed from the concealed tiles, lowest tile first
+        concealed: Vec<Meld>,
+        called: Vec<Meld>,
+    },
+    SevenPairs(Vec<MahjongTile>),
+    ThirteenOrphans { pair: MahjongTile },
+}
+
+impl Decomposition {
+    pub fn is_closed(&self) -> bool {
+        match self {
+            // A closed kan is set aside but leaves the hand closed
+            Decomposition::Standard { called, .. } => called.iter().all(|m| !m.is_open()),
+            _ => true,
+        }
+    }
+
+    // All sets in the hand, concealed ones first
+    pub fn melds(&self) -> Vec<Meld> {
+        match self {
+            Decomposition::Standard { concealed, called, .. } =>
+                concealed.iter().chain(called.iter()).copied().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+// `concealed` holds the tiles still in hand, including the winning tile, and
+// `called` the sets already called from other players
+pub fn decompositions(concealed: &HandCounts, called: &[Meld]) -> Vec<Decomposition> {
+    let mut found = standard_decompositions(concealed, called);
+    if called.is_empty() {
+        found.extend(seven_pairs(concealed));
+        found.extend(thirteen_orphans(concealed));
+    }
+    found
+}
+
+pub fn is_agari(concealed: &HandCounts, called: &[Meld]) -> bool {
+    !decompositions(concealed, called).is_empty()
+}
+
+fn standard_decompositions(concealed: &HandCounts, called: &[Meld]) -> Vec<Decomposition> {
+    if concealed.len() + 3 * called.len() != 14 {
+        return Vec::new();
+    }
+    let mut found = Vec::new();
+    for (index, count) in concealed.iter() {
+        if count < 2 {
+            continue;
+        }
+        let mut rest = *concealed;
+        rest.remove(index);
+        rest.remove(index);
+        for concealed in sets(&mut rest) {
+            found.push(Decomposition::Standard {
+                pair: index.tile(),
+                concealed,
+                called: called.to_vec(),
+            });
+        }
+    }
+    found
+}
+
+// Every way to split the counts entirely into pungs and chows
+fn sets(counts: &mut HandCounts) -> Vec<Vec<Meld>> {
+    let lowest = match counts.iter().next() {
+        Some((index, _)) => index,
+        None => return vec![Vec::new()],
+    };
+    let mut found = Vec::new();
+
+    if counts.count(lowest) >= 3 {
+        (0..3).for_each(|_| { counts.remove(lowest); });
+        for mut rest in sets(counts) {
+            rest.insert(0, Meld::Pung(Pung::new(lowest.tile())));
+            found.push(rest);
+        }
+        (0..3).for_each(|_| { counts.add(lowest); });
+    }
+
+    if let (Some(next), Some(after)) = (lowest.offset(1), lowest.offset(2)) {
+        if counts.contains(next) && counts.contains(after) {
+            let run = [lowest, next, after];
+            run.iter().for_each(|i| { counts.remove(*i); });
+            for mut rest in sets(counts) {
+                rest.insert(0, Meld::Chow(Chow::new(lowest.tile(), next.tile(), after.tile())));
+                found.push(rest);
+            }
+            run.iter().for_each(|i| { counts.add(*i); });
+        }
+    }
+
+    found
+}
+
+fn seven_pairs(concealed: &HandCounts) -> Option<Decomposition> {
+    let pairs: Vec<MahjongTile> = concealed.iter()
+        .filter(|(_, count)| *count == 2)
+        .map(|(index, _)| index.tile())
+        .collect();
+    match pairs.len() == 7 && concealed.len() == 14 {
+        true => Some(Decomposition::SevenPairs(pairs)),
+        false => None,
+    }
+}
+
+fn thirteen_orphans(concealed: &HandCounts) -> Option<Decomposition> {
+    let orphans = TileIndex::all().filter(|index| index.is_terminal_or_honour());
+    let mut pair = None;
+    for index in orphans {
+        match concealed.count(index) {
+            1 => (),
+            2 if pair.is_none() => pair = Some(index.tile()),
+            _ => return None,
+        }
+    }
+    match concealed.len() == 14 {
+        true => pair.map(|pair| Decomposition::ThirteenOrphans { pair }),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mahjong::tile::notation::{parse_hand, parse_tiles};
+
+    use super::*;
+
+    fn counts(notation: &str) -> HandCounts {
+        HandCounts::from_tiles(&parse_tiles(notation).unwrap())
+    }
+
+    #[test]
+    fn finds_every_reading() {
+        let found = decompositions(&counts("111222333m456p77z"), &[]);
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|d| d.melds().len() == 4));
+
+        // Also seven pairs
+        assert_eq!(decompositions(&counts("112233m445566p77z"), &[]).len(), 2);
+
+        // Four pungs, three 123m chows and a 444m pung, or a 111m pung and three 234m chows
+        assert_eq!(decompositions(&counts("111222333444m55p"), &[]).len(), 3);
+    }
+
+    #[test]
+    fn rejects_incomplete_hands() {
+        assert!(!is_agari(&counts("123789m345s77z467p"), &[]));
+        assert!(!is_agari(&counts("1111m2299p3388s77z"), &[]));
+        assert!(!is_agari(&counts("123m"), &[]));
+    }
+
+    #[test]
+    fn includes_called_melds() {
+        let hand = parse_hand("123m456p11z[789s][555z]").unwrap();
+        let found = decompositions(&HandCounts::from_tiles(&hand.tiles), &hand.melds);
+        assert_eq!(found.len(), 1);
+        assert!(!found[0].is_closed());
+        assert_eq!(found[0].melds().len(), 4);
+
+        let hand = parse_hand("123m456p789s11z(5555z)").unwrap();
+        assert!(decompositions(&HandCounts::from_tiles(&hand.tiles), &hand.melds)[0].is_closed());
+    }
+
+    #[test]
+    fn thirteen_orphans() {
+        assert_eq!(
+            decompositions(&counts("19m19p19s12345677z"), &[]),
+            vec![Decomposition::ThirteenOrphans { pair: "7z".parse().unwrap() }]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use crate::mahjong::strategy::block_strategy::Meld;
+use crate::mahjong::tile::{hand_counts::HandCounts, mahjong_tile::MahjongTile, tile_index::TileIndex};
+
+use super::agari::{decompositions, Decomposition};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WaitKind {
+    // Two-sided, 23 waiting on 1 or 4
+    Ryanmen,
+    // Closed, 13 waiting on 2
+    Kanchan,
+    // Edge, 12 waiting on 3 or 89 waiting on 7
+    Penchan,
+    // Two pairs, either of which can become the pung
+    Shanpon,
+    // A single tile waiting to become the pair
+    Tanki,
+    // 1234 waiting on 1 or 4 for the pair
+    Nobetan,
+}
+
+impl WaitKind {
+    // Waits that only have one tile kind to win on score fu for the wait
+    pub fn is_single_sided(&self) -> bool {
+        matches!(self, WaitKind::Kanchan | WaitKind::Penchan | WaitKind::Tanki | WaitKind::Nobetan)
+    }
+}
+
+// One way of winning on `tile`: the reading of the finished hand and the
+// shape the tile completed within it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wait {
+    pub tile: MahjongTile,
+    pub kind: WaitKind,
+    pub decomposition: Decomposition,
+}
+
+// Every wait of a tenpai hand, one entry per tile, reading and wait shape
+pub fn waits(concealed: &HandCounts, called: &[Meld]) -> Vec<Wait> {
+    let mut found = Vec::new();
+    for index in TileIndex::all() {
+        if concealed.count(index) >= 4 {
+            continue;
+        }
+        let mut completed = *concealed;
+        completed.add(index);
+        for decomposition in decompositions(&completed, called) {
+            for kind in classify(&decomposition, index.tile()) {
+                found.push(Wait { tile: index.tile(), kind, decomposition: decomposition.clone() });
+            }
+        }
+    }
+    found
+}
+
+// The distinct tiles a 13 tile hand can win on
+pub fn winning_tiles(concealed: &HandCounts, called: &[Meld]) -> Vec<MahjongTile> {
+    let mut tiles: Vec<MahjongTile> = waits(concealed, called).into_iter().map(|w| w.tile).collect();
+    tiles.dedup();
+    tiles
+}
+
+pub fn is_tenpai(concealed: &HandCounts, called: &[Meld]) -> bool {
+    !winning_tiles(concealed, called).is_empty()
+}
+
+// The wait shapes `tile` could have completed in a finished hand
+pub fn classify(decomposition: &Decomposition, tile: MahjongTile) -> Vec<WaitKind> {
+    let (pair, concealed) = match decomposition {
+        Decomposition::Standard { pair, concealed, .. } => (pair, concealed),
+        _ => return vec![WaitKind::Tanki],
+    };
+    let mut kinds = Vec::new();
+    if *pair == tile {
+        match is_nobetan(tile, concealed) {
+            true => kinds.push(WaitKind::Nobetan),
+            false => kinds.push(WaitKind::Tanki),
+        }
+    }
+    for meld in concealed {
+        let kind = match meld {
+            Meld::Pung(_) if meld.tiles()[0] == tile => WaitKind::Shanpon,
+            Meld::Chow(_) => match chow_wait(meld.tiles(), tile) {
+                Some(kind) => kind,
+                None => continue,
+            },
+            _ => continue,
+        };
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    kinds
+}
+
+fn chow_wait(tiles: &[MahjongTile], tile: MahjongTile) -> Option<WaitKind> {
+    let low = TileIndex::try_from(tiles[0]).ok()?.value()?;
+    match tiles.iter().position(|t| *t == tile)? {
+        1 => Some(WaitKind::Kanchan),
+        0 if low == 7 => Some(WaitKind::Penchan),
+        2 if low == 1 => Some(WaitKind::Penchan),
+        _ => Some(WaitKind::Ryanmen),
+    }
+}
+
+// The pair tile sitting at either end of a concealed run, making a four tile
+// run that also waits on its other end
+fn is_nobetan(tile: MahjongTile, concealed: &[Meld]) -> bool {
+    let Ok(index) = TileIndex::try_from(tile) else {
+        return false;
+    };
+    concealed.iter().any(|meld| match meld {
+        Meld::Chow(_) => {
+            let low = TileIndex::try_from(meld.tiles()[0]).ok();
+            low.is_some() && (index.offset(1) == low || index.offset(-3) == low)
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mahjong::tile::notation::parse_tiles;
+
+    use super::*;
+
+    fn counts(notation: &str) -> HandCounts {
+        HandCounts::from_tiles(&parse_tiles(notation).unwrap())
+    }
+
+    fn kinds(notation: &str, tile: &str) -> Vec<WaitKind> {
+        let tile: MahjongTile = tile.parse().unwrap();
+        waits(&counts(notation), &[]).into_iter()
+            .filter(|w| w.tile == tile)
+            .map(|w| w.kind)
+            .collect()
+    }
+
+    #[test]
+    fn classifies_simple_waits() {
+        assert_eq!(kinds("123456789m23p11z", "1p"), vec![WaitKind::Ryanmen]);
+        assert_eq!(kinds("123456789m23p11z", "4p"), vec![WaitKind::Ryanmen]);
+        assert_eq!(kinds("123456789m13p11z", "2p"), vec![WaitKind::Kanchan]);
+        assert_eq!(kinds("123456789m12p11z", "3p"), vec![WaitKind::Penchan]);
+        assert_eq!(kinds("123456789m89p11z", "7p"), vec![WaitKind::Penchan]);
+        assert_eq!(kinds("123456789m99p11z", "9p"), vec![WaitKind::Shanpon]);
+        assert_eq!(kinds("123456789m123p1z", "1z"), vec![WaitKind::Tanki]);
+        assert_eq!(kinds("1155m2299p3388s7z", "7z"), vec![WaitKind::Tanki]);
+    }
+
+    #[test]
+    fn nobetan() {
+        let hand = counts("123456789m1234p");
+        assert_eq!(winning_tiles(&hand, &[]), parse_tiles("14p").unwrap());
+        assert_eq!(kinds("123456789m1234p", "1p"), vec![WaitKind::Nobetan]);
+        assert_eq!(kinds("123456789m1234p", "4p"), vec![WaitKind::Nobetan]);
+    }
+
+    #[test]
+    fn multiple_readings() {
+        // 4556p + 55p reads as 456p + 55p, or as a 555p pung with 46p waiting on 5p
+        let hand = counts("123789m11z45556p");
+        assert_eq!(winning_tiles(&hand, &[]), parse_tiles("5p1z").unwrap());
+        assert_eq!(kinds("123789m11z45556p", "5p"), vec![WaitKind::Kanchan, WaitKind::Shanpon]);
+        assert_eq!(kinds("123789m11z45556p", "1z"), vec![WaitKind::Shanpon]);
+
+        // Nine gates waits on every tile of its suit
+        let hand = counts("1112345678999m");
+        assert_eq!(winning_tiles(&hand, &[]), parse_tiles("123456789m").unwrap());
+    }
+
+    #[test]
+    fn not_tenpai() {
+        assert!(!is_tenpai(&counts("147m258p369s1234z"), &[]));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod agari;
+pub mod machi;
+pub mod shanten;
+pub mod ukeire;
@@ -0,0 +1,249 @@
+use std::cell::RefCell;
+use std::cmp::min;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::mahjong::tile::hand_counts::HandCounts;
+
+// Shanten is the number of tile swaps a hand is away from tenpai: 0 is tenpai
+// and -1 is a complete hand. `called_melds` is the number of sets already
+// called from other players, which no longer appear in the concealed counts.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shanten {
+    pub regular: i8,
+    // Seven pairs and thirteen orphans only exist for fully concealed hands
+    pub chiitoitsu: Option<i8>,
+    pub kokushi: Option<i8>,
+}
+
+impl Shanten {
+    pub fn min(&self) -> i8 {
+        [Some(self.regular), self.chiitoitsu, self.kokushi]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap()
+    }
+}
+
+pub fn shanten(hand: &HandCounts, called_melds: usize) -> i8 {
+    shanten_forms(hand, called_melds).min()
+}
+
+pub fn shanten_forms(hand: &HandCounts, called_melds: usize) -> Shanten {
+    let closed = called_melds == 0;
+    Shanten {
+        regular: regular_shanten(hand, called_melds),
+        chiitoitsu: closed.then(|| chiitoitsu_shanten(hand)),
+        kokushi: closed.then(|| kokushi_shanten(hand)),
+    }
+}
+
+// Four sets and a pair: 8 - 2 * sets - partial sets - pair, with no more than
+// four sets and partial sets counted between them
+pub fn regular_shanten(hand: &HandCounts, called_melds: usize) -> i8 {
+    let wanted = 4u8.saturating_sub(called_melds as u8);
+    let counts = hand.as_array();
+    let groups = [
+        suit_blocks(&counts[0..9]),
+        suit_blocks(&counts[9..18]),
+        suit_blocks(&counts[18..27]),
+        Rc::new(honour_blocks(&counts[27..34])),
+    ];
+
+    let mut best = 0;
+    // Try the hand with no pair, then with the pair taken from each group
+    for pair_group in 0..=groups.len() {
+        let mut totals = vec![(0u8, 0u8)];
+        for (group, blocks) in groups.iter().enumerate() {
+            let options = match group == pair_group {
+                true => &blocks.with_pair,
+                false => &blocks.without_pair,
+            };
+            totals = totals.iter()
+                .flat_map(|(sets, partials)| options.iter().map(move |(s, p)| (sets + s, partials + p)))
+                .collect();
+        }
+        let pair = (pair_group < groups.len()) as i8;
+        for (sets, partials) in totals {
+            let sets = min(sets, wanted);
+            let partials = min(partials, wanted - sets);
+            best = best.max((2 * sets + partials) as i8 + pair);
+        }
+    }
+    2 * wanted as i8 - best
+}
+
+pub fn chiitoitsu_shanten(hand: &HandCounts) -> i8 {
+    let pairs = hand.iter().filter(|(_, count)| *count >= 2).count() as i8;
+    let kinds = hand.iter().count() as i8;
+    6 - pairs + (7 - kinds).max(0)
+}
+
+pub fn kokushi_shanten(hand: &HandCounts) -> i8 {
+    let orphans: Vec<u8> = hand.iter()
+        .filter(|(index, _)| index.is_terminal_or_honour())
+        .map(|(_, count)| count)
+        .collect();
+    let has_pair = orphans.iter().any(|count| *count >= 2);
+    13 - orphans.len() as i8 - has_pair as i8
+}
+
+// The (sets, partial sets) counts worth considering for one group of tiles,
+// with and without taking the hand's pair from it. Dominated options are dropped.
+#[derive(Debug, Default)]
+struct Blocks {
+    without_pair: Vec<(u8, u8)>,
+    with_pair: Vec<(u8, u8)>,
+}
+
+impl Blocks {
+    fn insert(&mut self, sets: u8, partials: u8, pair: bool) {
+        let options = match pair {
+            true => &mut self.with_pair,
+            false => &mut self.without_pair,
+        };
+        if options.iter().any(|(s, p)| *s >= sets && *p >= partials) {
+            return;
+        }
+        options.retain(|(s, p)| !(sets >= *s && partials >= *p));
+        options.push((sets, partials));
+    }
+}
+
+thread_local! {
+    static SUIT_CACHE: RefCell<HashMap<u32, Rc<Blocks>>> = RefCell::new(HashMap::new());
+}
+
+fn suit_blocks(counts: &[u8]) -> Rc<Blocks> {
+    let key = counts.iter().fold(0u32, |key, count| key * 5 + *count as u32);
+    SUIT_CACHE.with(|cache| {
+        if let Some(blocks) = cache.borrow().get(&key) {
+            return blocks.clone();
+        }
+        let mut suit = [0u8; 9];
+        suit.copy_from_slice(counts);
+        let mut blocks = Blocks::default();
+        search_suit(&mut suit, 0, 0, 0, false, &mut blocks);
+        let blocks = Rc::new(blocks);
+        cache.borrow_mut().insert(key, blocks.clone());
+        blocks
+    })
+}
+
+fn search_suit(counts: &mut [u8; 9], index: usize, sets: u8, partials: u8, pair: bool, blocks: &mut Blocks) {
+    let mut index = index;
+    while index < 9 && counts[index] == 0 {
+        index += 1;
+    }
+    if index == 9 {
+        blocks.insert(sets, partials, pair);
+        return;
+    }
+
+    if counts[index] >= 3 {
+        counts[index] -= 3;
+        search_suit(counts, index, sets + 1, partials, pair, blocks);
+        counts[index] += 3;
+    }
+    if index + 2 < 9 && counts[index + 1] > 0 && counts[index + 2] > 0 {
+        take(counts, &[index, index + 1, index + 2]);
+        search_suit(counts, index, sets + 1, partials, pair, blocks);
+        give(counts, &[index, index + 1, index + 2]);
+    }
+    if counts[index] >= 2 {
+        counts[index] -= 2;
+        search_suit(counts, index, sets, partials + 1, pair, blocks);
+        if !pair {
+            search_suit(counts, index, sets, partials, true, blocks);
+        }
+        counts[index] += 2;
+    }
+    for other in [index + 1, index + 2] {
+        if other < 9 && counts[other] > 0 {
+            take(counts, &[index, other]);
+            search_suit(counts, index, sets, partials + 1, pair, blocks);
+            give(counts, &[index, other]);
+        }
+    }
+    // Leave one copy of this tile as an isolated tile
+    counts[index] -= 1;
+    search_suit(counts, index, sets, partials, pair, blocks);
+    counts[index] += 1;
+}
+
+// Honours can't form runs, so each kind only ever makes a pung, a pair or
+// nothing on its own
+fn honour_blocks(counts: &[u8]) -> Blocks {
+    let sets = counts.iter().filter(|c| **c >= 3).count() as u8;
+    let pairs = counts.iter().filter(|c| **c == 2).count() as u8;
+    let mut blocks = Blocks::default();
+    blocks.insert(sets, pairs, false);
+    for count in counts {
+        match count {
+            2 => blocks.insert(sets, pairs - 1, true),
+            3 => blocks.insert(sets - 1, pairs, true),
+            4 => blocks.insert(sets - 1, pairs + 1, true),
+            _ => (),
+        }
+    }
+    blocks
+}
+
+fn take(counts: &mut [u8], indices: &[usize]) {
+    indices.iter().for_each(|i| counts[*i] -= 1);
+}
+
+fn give(counts: &mut [u8], indices: &[usize]) {
+    indices.iter().for_each(|i| counts[*i] += 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mahjong::tile::notation::parse_tiles;
+
+    use super::*;
+
+    fn counts(notation: &str) -> HandCounts {
+        HandCounts::from_tiles(&parse_tiles(notation).unwrap())
+    }
+
+    #[test]
+    fn regular_hands() {
+        assert_eq!(regular_shanten(&counts("123456789m123p11z"), 0), -1);
+        assert_eq!(regular_shanten(&counts("123456789m12p11z"), 0), 0);
+        assert_eq!(regular_shanten(&counts("123456789m1p111z"), 0), 0);
+        assert_eq!(regular_shanten(&counts("123456789m159p1z"), 0), 2);
+        assert_eq!(regular_shanten(&counts("147m258p369s1234z"), 0), 8);
+        // 111222333m reads as pungs or chows, both complete
+        assert_eq!(regular_shanten(&counts("111222333m456p77z"), 0), -1);
+    }
+
+    #[test]
+    fn called_melds_are_subtracted() {
+        assert_eq!(regular_shanten(&counts("123m11z"), 3), -1);
+        assert_eq!(regular_shanten(&counts("12m11z"), 3), 0);
+        assert_eq!(regular_shanten(&counts("1z"), 4), 0);
+        assert_eq!(shanten_forms(&counts("12m11z"), 3).chiitoitsu, None);
+    }
+
+    #[test]
+    fn seven_pairs() {
+        assert_eq!(chiitoitsu_shanten(&counts("1155m2299p3388s77z")), -1);
+        assert_eq!(chiitoitsu_shanten(&counts("1155m2299p3388s7z")), 0);
+        // Four of a kind only counts as one pair
+        assert_eq!(chiitoitsu_shanten(&counts("1111m2299p3388s7z")), 2);
+        assert_eq!(shanten(&counts("1155m2299p3388s7z"), 0), 0);
+    }
+
+    #[test]
+    fn thirteen_orphans() {
+        assert_eq!(kokushi_shanten(&counts("19m19p19s12345677z")), -1);
+        assert_eq!(kokushi_shanten(&counts("19m19p19s1234567z")), 0);
+        assert_eq!(kokushi_shanten(&counts("19m19p19s1234566z")), 0);
+        assert_eq!(kokushi_shanten(&counts("258m19p19s123456z")), 3);
+        assert_eq!(shanten(&counts("19m19p19s1234567z"), 0), 0);
+    }
+}
+
@@ -0,0 +1,104 @@
+use crate::mahjong::strategy::block_strategy::Meld;
+use crate::mahjong::tile::{hand_counts::HandCounts, mahjong_tile::MahjongTile, tile_index::TileIndex};
+
+use super::shanten::shanten;
+
+// Tiles that would move a hand closer to tenpai, with the number of copies
+// of each the player hasn't seen yet
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Acceptance {
+    pub tiles: Vec<(MahjongTile, u8)>,
+}
+
+impl Acceptance {
+    pub fn total(&self) -> u32 {
+        self.tiles.iter().map(|(_, unseen)| *unseen as u32).sum()
+    }
+
+    pub fn kinds(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscardOption {
+    pub discard: MahjongTile,
+    // Shanten of the hand left behind
+    pub shanten: i8,
+    pub acceptance: Acceptance,
+}
+
+// Every tile the player can account for: their own hand, every discard on the
+// table, open melds and the revealed dora indicators
+pub fn visible_tiles(hand: &[MahjongTile], discards: &[MahjongTile], melds: &[Meld], dora_indicators: &[MahjongTile]) -> HandCounts {
+    hand.iter()
+        .chain(discards)
+        .chain(melds.iter().flat_map(|m| m.tiles()))
+        .chain(dora_indicators)
+        .copied()
+        .collect()
+}
+
+// The acceptance of a 13 tile hand (less three per called meld)
+pub fn ukeire(hand: &HandCounts, called_melds: usize, visible: &HandCounts) -> Acceptance {
+    let current = shanten(hand, called_melds);
+    let tiles = TileIndex::all()
+        .filter(|index| hand.count(*index) < 4)
+        .filter(|index| {
+            let mut drawn = *hand;
+            drawn.add(*index);
+            shanten(&drawn, called_melds) < current
+        })
+        .map(|index| (index.tile(), 4u8.saturating_sub(visible.count(index))))
+        .collect();
+    Acceptance { tiles }
+}
+
+// Each distinct discard from a 14 tile hand, best first: lowest shanten, then
+// most tiles accepted
+pub fn discard_options(hand: &HandCounts, called_melds: usize, visible: &HandCounts) -> Vec<DiscardOption> {
+    let mut options: Vec<DiscardOption> = hand.iter()
+        .map(|(index, _)| {
+            let mut remaining = *hand;
+            remaining.remove(index);
+            DiscardOption {
+                discard: index.tile(),
+                shanten: shanten(&remaining, called_melds),
+                acceptance: ukeire(&remaining, called_melds, visible),
+            }
+        })
+        .collect();
+    options.sort_by_key(|o| (o.shanten, std::cmp::Reverse(o.acceptance.total())));
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mahjong::tile::notation::parse_tiles;
+
+    use super::*;
+
+    fn counts(notation: &str) -> HandCounts {
+        HandCounts::from_tiles(&parse_tiles(notation).unwrap())
+    }
+
+    #[test]
+    fn counts_unseen_copies() {
+        let hand = parse_tiles("123456789m23p11z").unwrap();
+        let visible = visible_tiles(&hand, &parse_tiles("1p1p4p").unwrap(), &[], &parse_tiles("4p").unwrap());
+        let acceptance = ukeire(&HandCounts::from_tiles(&hand), 0, &visible);
+        assert_eq!(acceptance.tiles, vec![("1p".parse().unwrap(), 2), ("4p".parse().unwrap(), 2)]);
+        assert_eq!(acceptance.total(), 4);
+    }
+
+    #[test]
+    fn prefers_wider_discards() {
+        // Throwing 1z waits on 1p/4p, throwing 2p or 3p leaves a single tile wait
+        let hand = counts("123456789m23p111z");
+        let options = discard_options(&hand, 0, &hand);
+        assert_eq!(options[0].shanten, 0);
+        assert_eq!(options[0].discard, "1z".parse().unwrap());
+        assert_eq!(options[0].acceptance.total(), 8);
+        assert!(options.windows(2).all(|pair| pair[0].shanten <= pair[1].shanten));
+    }
+}
@@ -1,102 +1,1405 @@
 
-use strum::IntoEnumIterator;
+use std::fmt;
+
 use crate::mahjong::tile::enums::*;
 
-use super::player::player::TurnState;
-use super::player_state::player_state::PlayerState;
-use super::tile::mahjong_tile::MahjongTile;
-use super::wall::wall::Wall;
+use super::analysis::ukeire::visible_tiles;
+use super::mcr::score::{best_score as best_mcr_score, McrScore};
+use super::player::player::{DiscardResponse, TableView, TurnState};
+use super::strategy::block_strategy::Meld;
+use super::player_state::player_state::{PlayerState, RIICHI_STICK};
+use super::ruleset::ruleset::{Ruleset, SanmaTsumo, Scoring};
+use super::table::table::{Reason, Settlement, TableState, HONBA, NOTEN_PENALTY, SANMA_NOTEN_PENALTY};
+use super::yaku::score::Limit;
+use super::tile::mahjong_tile::{MahjongTile, Nextable};
+use super::wall::{dead_wall::DeadWall, tile_set::TileSet, wall::Wall};
+use super::yaku::{context::WinContext, score::{best_score, Dora, Score}};
 
 
 pub struct Game {
+    ruleset: Ruleset,
+    tile_set: TileSet,
+    seed: Option<u64>,
     wall: Wall,
-    dead_wall: Vec<MahjongTile>,
+    dead_wall: DeadWall,
     players: Vec<PlayerState>,
+    // The round wind
     current_round: Wind,
+    // Whose turn it is
+    current_turn: Wind,
+    next_draw: NextDraw,
+    // Riichi deposits on the table
+    riichi_sticks: u32,
+    honba: u32,
+    // Every point transfer so far this hand
+    settlement: Settlement,
+    // What each winning hand was worth, once someone has won
+    scores: Vec<(Wind, HandScore)>,
+}
+
+// Where the player whose turn it is gets their tile from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NextDraw {
+    Wall,
+    // The replacement tile after a kan
+    DeadWall,
+    // Chi and pon are followed straight away by a discard
+    Skip,
+}
+
+// Why a tile is being offered to the other seats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Offer {
+    Discard,
+    // Only ron may be claimed, robbing the kan (chankan)
+    AddedKan,
 }
 
 impl Game {
-    pub fn new() -> Self {
-        let mut wall = Wall::new();
+    // Deals from a shuffled wall of the ruleset's tiles: its red fives, with
+    // the sanma set or flowers if it calls for them
+    pub fn new(ruleset: Ruleset) -> Self {
+        let wall = Wall::from_tile_set(&ruleset.tile_set());
+        Self::with_wall(ruleset, wall)
+    }
+
+    // Replays the game dealt from `seed`
+    pub fn with_seed(ruleset: Ruleset, seed: u64) -> Self {
+        let wall = Wall::from_tile_set_with_seed(&ruleset.tile_set(), seed);
+        Self::with_wall(ruleset, wall)
+    }
+
+    pub fn with_wall(ruleset: Ruleset, wall: Wall) -> Self {
+        let table = TableState::with_starting_points(ruleset.starting_points);
+        Self::with_table(ruleset, wall, table)
+    }
+
+    // A hand picking up the points, sticks and counters left by the last one
+    pub fn with_table(ruleset: Ruleset, mut wall: Wall, table: TableState) -> Self {
+        let seed = wall.seed();
         let dead_wall = wall.split_dead_wall();
-        let players : Vec<PlayerState> = Wind::iter().map ( |w| PlayerState::new(w) ).collect();
-        Game {
+        let players : Vec<PlayerState> = ruleset.seats().into_iter()
+            .map(|wind| {
+                let mut player_state = PlayerState::new(wind);
+                player_state.set_points(table.points(wind));
+                player_state
+            })
+            .collect();
+        let mut game = Game {
+            ruleset,
+            tile_set: ruleset.tile_set(),
+            seed,
             wall,
             dead_wall,
             players,
-            current_round: Wind::East,
+            current_round: table.round_wind,
+            current_turn: Wind::East,
+            next_draw: NextDraw::Wall,
+            riichi_sticks: table.riichi_sticks,
+            honba: table.honba,
+            settlement: Settlement::default(),
+            scores: Vec::new(),
+        };
+        game.deal();
+        game.check_tiles();
+        game
+    }
+
+    // Haipai: three rounds of four tiles to each seat starting with the dealer,
+    // then one more each. The dealer's fourteenth tile is their first draw.
+    fn deal(&mut self) {
+        for handful in [4, 4, 4, 1] {
+            let mut seat = Wind::East;
+            for _ in 0..self.players.len() {
+                let drawn: Vec<MahjongTile> = (0..handful).filter_map(|_| self.wall.draw()).collect();
+                let tiles: Vec<MahjongTile> = drawn.into_iter().filter_map(|tile| self.replace_flowers(seat, tile)).collect();
+                self.player_mut(seat).deal(&tiles);
+                seat = self.next_seat(seat);
+            }
+        }
+    }
+
+    // Sets aside any flower `seat` gets, drawing a replacement from the back
+    // of the wall until it's a tile that can go in the hand
+    fn replace_flowers(&mut self, seat: Wind, mut tile: MahjongTile) -> Option<MahjongTile> {
+        while tile.is_flower() {
+            self.player_mut(seat).set_aside_flower(tile);
+            tile = self.wall.draw_from_tail()?;
+        }
+        Some(tile)
+    }
+
+    // The seat after `seat`, skipping North in sanma
+    fn next_seat(&self, seat: Wind) -> Wind {
+        match seat.next() {
+            Wind::North if self.ruleset.sanma => Wind::East,
+            next => next,
+        }
+    }
+
+    pub fn player(&self, wind: Wind) -> &PlayerState {
+        self.players.iter().find(|x| x.wind() == wind).unwrap()
+    }
+
+    fn player_mut(&mut self, wind: Wind) -> &mut PlayerState {
+        self.players.iter_mut().find(|x| x.wind() == wind).unwrap()
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn dead_wall(&self) -> &DeadWall {
+        &self.dead_wall
+    }
+
+    pub fn riichi_sticks(&self) -> u32 {
+        self.riichi_sticks
+    }
+
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    pub fn current_round(&self) -> Wind {
+        self.current_round
+    }
+
+    pub fn honba(&self) -> u32 {
+        self.honba
+    }
+
+    // Each winner with their hand's value, more than one after a double ron
+    pub fn scores(&self) -> &[(Wind, HandScore)] {
+        &self.scores
+    }
+
+    pub fn settlement(&self) -> &Settlement {
+        &self.settlement
+    }
+
+    // What to carry into the next hand. Once the hand is over the honba
+    // counter is already the next hand's.
+    pub fn table(&self) -> TableState {
+        TableState {
+            round_wind: self.current_round,
+            points: self.players.iter().map(|p| (p.wind(), p.points())).collect(),
+            riichi_sticks: self.riichi_sticks,
+            honba: self.honba,
         }
     }
 
-    pub fn run(&mut self) {
-        while self.wall.has_tiles() {
+    // Plays the hand out, settling the points for a win
+    pub fn run(&mut self) -> Outcome {
+        loop {
+            if !self.wall.has_tiles() {
+                return self.exhaustive_draw();
+            }
             match self.turn() {
-                GameTurnState::Win(wind) => {
-                    println!("{:?} wins", wind);
-                    return;
+                GameTurnState::Tsumo(wind) => {
+                    self.settle(&[wind], None);
+                    return Outcome::Tsumo(wind);
                 }
-                GameTurnState::Chombo(wind) => {
-                    println!("{:?} chombo'ed", wind);
-                    return;
+                GameTurnState::Ron { winner, discarder } => {
+                    self.settle(&[winner], Some(discarder));
+                    return Outcome::Ron { winner, discarder };
                 }
-                _ => (),
+                GameTurnState::DoubleRon { winners, discarder } => {
+                    self.settle(&winners, Some(discarder));
+                    return Outcome::DoubleRon { winners, discarder };
+                }
+                GameTurnState::Chombo(wind) => return Outcome::Chombo(wind),
+                GameTurnState::Abort(abortive_draw) => {
+                    self.honba += 1;
+                    return Outcome::AbortiveDraw(abortive_draw);
+                }
+                GameTurnState::None => (),
             }
         }
-        println!("Exhaustive Draw");
     }
 
     fn turn(&mut self) -> GameTurnState {
-        let player_state = self.players.iter_mut().find(|x| x.wind() == self.current_round).unwrap();
-        if let Some(tile) = self.wall.draw() {
-            match player_state.turn(tile) {
-                TurnState::Tsumo => {
-                    println!("We got a tsumo from {:?}", self.current_round);
-                    return GameTurnState::Win(self.current_round);
-                }
-                TurnState::Discard(discarded) => {
-                    match player_state.discard(discarded) {
-                        Ok(_tile) => (),
-                        Err(_) => return GameTurnState::Chombo(player_state.wind()),
-                    }
-                }
-                TurnState::Draw => (),
-                TurnState::Riichi(_) => todo!(),
-            }
-        }
-
-        self.progress_round();
-        return GameTurnState::None;
-    }
-
-    // fn handle_discard(&mut self, tile: MahjongTile) -> GameTurnState {
-    //     for player_state in &mut self.players {
-    //         if player_state.wind != self.current_round {
-    //             // match player_state.player.offer_discard(tile) {
-    //             //     DiscardState::None => (),
-    //             //     DiscardState::Ron => {
-    //             //         println!("We got a ron from {:?}", player_state.wind);
-    //             //         return GameTurnState::Win(player_state.wind);
-    //             //     }
-    //             // }
-    //         }
-    //     }
-    //     let current_player = self.players.iter_mut().find(|x| x.wind == self.current_round).unwrap();
-    //     current_player.discards.push(tile);
-    //     GameTurnState::None
-    // }
-
-    fn progress_round(&mut self) {
-        self.current_round = match self.current_round {
-            Wind::East => Wind::North,
-            Wind::North => Wind::West,
-            Wind::West => Wind::South,
-            Wind::South => Wind::East,
+        let seat = self.current_turn;
+        let mut table = self.table_view(seat);
+        let draw = std::mem::replace(&mut self.next_draw, NextDraw::Wall);
+        let drawn = match draw {
+            NextDraw::Wall => self.wall.draw(),
+            NextDraw::DeadWall => self.dead_wall.draw_rinshan(&mut self.wall),
+            NextDraw::Skip => None,
+        };
+        let flowers = self.player(seat).flowers().len();
+        let drawn = drawn.and_then(|tile| self.replace_flowers(seat, tile));
+        // Winning on a flower's replacement counts like winning on a kan's
+        let replaced = self.player(seat).flowers().len() > flowers;
+        let riichi = self.player(seat).riichi().is_some();
+        table.riichi = riichi;
+        table.can_kan = draw != NextDraw::Skip && !riichi && self.kans_allowed();
+        table.can_riichi = self.ruleset.scoring == Scoring::Riichi && draw != NextDraw::Skip && !riichi
+            && self.player(seat).is_closed()
+            && self.player(seat).points() >= RIICHI_STICK
+            && self.wall.remaining() >= 4;
+        // Double riichi is declared with the first discard, before any call
+        let first_turn = self.player(seat).discards().is_empty() && self.players.iter().all(|p| p.melds().is_empty());
+        let tsumo = drawn.and_then(|tile| {
+            let context = WinContext {
+                tsumo: true,
+                rinshan: draw == NextDraw::DeadWall || replaced,
+                last_tile: draw == NextDraw::Wall && !self.wall.has_tiles(),
+                first_draw: draw == NextDraw::Wall && first_turn,
+                ..self.win_context(seat)
+            };
+            self.winning_score(seat, tile, &context)
+        });
+        table.can_tsumo = tsumo.is_some();
+        // Kyuushu kyuuhai: nine kinds of terminal and honour on a first draw
+        // nobody has called before
+        table.can_kyuushu = self.ruleset.abortive_draws.kyuushu_kyuuhai && draw == NextDraw::Wall && first_turn
+            && drawn.is_some_and(|tile| {
+                let mut hand = *self.player(seat).hand_counts();
+                hand.add(tile);
+                hand.iter().filter(|(index, _)| index.is_terminal_or_honour()).count() >= 9
+            });
+        // Kita: in riichi only a North just drawn can go, so the wait stays
+        let north = MahjongTile::Wind(Wind::North);
+        table.can_nukidora = self.ruleset.sanma && draw != NextDraw::Skip && self.wall.has_tiles()
+            && match riichi {
+                true => drawn == Some(north),
+                false => drawn == Some(north) || self.player(seat).hand_counts().contains(north),
+            };
+        let (can_kan, can_riichi, can_tsumo, can_kyuushu) = (table.can_kan, table.can_riichi, table.can_tsumo, table.can_kyuushu);
+        let can_nukidora = table.can_nukidora;
+        let player_state = self.player_mut(seat);
+        let state = match drawn {
+            Some(tile) => player_state.turn(tile, &table),
+            None if draw == NextDraw::Skip => player_state.turn_after_call(&table),
+            None => return GameTurnState::None,
+        };
+        match state {
+            TurnState::Tsumo => {
+                // A complete hand without a yaku doesn't win either
+                if !can_tsumo {
+                    return GameTurnState::Chombo(seat);
+                }
+                self.scores = tsumo.into_iter().map(|score| (seat, score)).collect();
+                return GameTurnState::Tsumo(seat);
+            }
+            TurnState::KyuushuKyuuhai => {
+                if !can_kyuushu {
+                    return GameTurnState::Chombo(seat);
+                }
+                return GameTurnState::Abort(AbortiveDraw::KyuushuKyuuhai(seat));
+            }
+            TurnState::Discard(discarded) => {
+                // In riichi the drawn tile is the only one that can go
+                let discarded = match (riichi, drawn) {
+                    (false, _) => discarded,
+                    (true, Some(tile)) if tile == discarded => tile,
+                    (true, _) => return GameTurnState::Chombo(seat),
+                };
+                match player_state.discard(discarded) {
+                    Ok(_tile) => (),
+                    Err(_) => return GameTurnState::Chombo(player_state.wind()),
+                }
+                match self.handle_discard(seat, discarded) {
+                    GameTurnState::None => (),
+                    outcome => return outcome,
+                }
+                if let Some(abortive_draw) = self.abortive_draw() {
+                    return GameTurnState::Abort(abortive_draw);
+                }
+            }
+            TurnState::ClosedKan(tile) => {
+                if !can_kan || player_state.closed_kan(tile).is_err() {
+                    return GameTurnState::Chombo(seat);
+                }
+                self.kan();
+            }
+            TurnState::AddedKan(tile) => {
+                if !can_kan || player_state.added_kan(tile).is_err() {
+                    return GameTurnState::Chombo(seat);
+                }
+                // Chankan: the added tile can be robbed for ron, though not
+                // called for anything else
+                match self.offer_tile(seat, tile, Offer::AddedKan) {
+                    GameTurnState::None => (),
+                    outcome => return outcome,
+                }
+                self.kan();
+            }
+            TurnState::Nukidora => {
+                if !can_nukidora || player_state.extract_nukidora().is_err() {
+                    return GameTurnState::Chombo(seat);
+                }
+                self.break_ippatsu();
+                self.next_draw = NextDraw::DeadWall;
+            }
+            TurnState::Riichi(discarded) => {
+                if !can_riichi || player_state.declare_riichi(discarded, first_turn).is_err() {
+                    return GameTurnState::Chombo(seat);
+                }
+                match self.handle_discard(seat, discarded) {
+                    GameTurnState::None => (),
+                    outcome => return outcome,
+                }
+                self.riichi_sticks += 1;
+                self.transfer(Some(seat), None, RIICHI_STICK as u32, Reason::RiichiDeposit);
+                if let Some(abortive_draw) = self.abortive_draw() {
+                    return GameTurnState::Abort(abortive_draw);
+                }
+            }
+        }
+
+        // A call hands the turn straight to the caller, and a kan leaves it
+        // with the player who made it
+        if self.next_draw == NextDraw::Wall {
+            self.progress_turn();
+        }
+        self.check_tiles();
+        GameTurnState::None
+    }
+
+    // Every tile of the set must be somewhere: wall, dead wall, a hand or the discards
+    fn check_tiles(&self) {
+        if cfg!(debug_assertions) {
+            let mut tiles: Vec<MahjongTile> = self.wall.tiles().to_vec();
+            tiles.extend(self.dead_wall.tiles());
+            for player in &self.players {
+                tiles.extend_from_slice(player.hand());
+                tiles.extend_from_slice(player.discards());
+                tiles.extend_from_slice(player.nukidora());
+                tiles.extend_from_slice(player.flowers());
+                // Called tiles stay counted among the discards
+                tiles.extend(player.melds().iter().flat_map(|m| m.from_hand()));
+            }
+            if let Err(problem) = self.tile_set.verify(&tiles) {
+                panic!("tiles have gone astray: {}", problem);
+            }
+        }
+    }
+
+    // What `seat` can see of the table
+    fn table_view(&self, seat: Wind) -> TableView {
+        let shown: Vec<MahjongTile> = self.players.iter()
+            .flat_map(|p| p.discards().iter().chain(p.nukidora()).copied().chain(p.melds().iter().flat_map(|m| m.from_hand())))
+            .collect();
+        // Nothing is turned over for dora under MCR, and a flower can't be counted
+        let indicators = match self.ruleset.scoring {
+            Scoring::Riichi => self.dead_wall.dora_indicators(),
+            Scoring::Mcr => &[],
         };
+        TableView {
+            visible: visible_tiles(&[], &shown, &[], indicators),
+            melds: self.player(seat).melds().to_vec(),
+            can_kan: false,
+            can_tsumo: false,
+            can_kyuushu: false,
+            can_nukidora: false,
+            riichi: self.player(seat).riichi().is_some(),
+            can_riichi: false,
+            furiten: self.player(seat).is_furiten(),
+        }
+    }
+
+    // Another kan needs a replacement tile to draw and a dora indicator to flip
+    fn kans_allowed(&self) -> bool {
+        self.wall.has_tiles() && self.dead_wall.dora_indicators().len() < 5
+    }
+
+    // Flips a kan dora and has the player draw their replacement from the
+    // dead wall. The indicator is turned over straight away, whatever the kan.
+    fn kan(&mut self) {
+        self.break_ippatsu();
+        self.dead_wall.reveal_kan_dora();
+        self.next_draw = NextDraw::DeadWall;
+    }
+
+    fn handle_discard(&mut self, discarder: Wind, tile: MahjongTile) -> GameTurnState {
+        self.offer_tile(discarder, tile, Offer::Discard)
+    }
+
+    // How `seat` stands for a win, before anything about the winning tile
+    fn win_context(&self, seat: Wind) -> WinContext {
+        let riichi = self.player(seat).riichi();
+        WinContext {
+            riichi: riichi.is_some(),
+            double_riichi: riichi.is_some_and(|r| r.double),
+            ippatsu: riichi.is_some_and(|r| r.ippatsu),
+            ..WinContext::new(seat, self.current_round)
+        }
+    }
+
+    // What `seat` would win on `tile`, None if the hand isn't complete or
+    // has no yaku. Ura-dora only count for a riichi hand, and each North set
+    // aside is a dora of its own as well as being counted like a hand tile.
+    fn winning_score(&self, seat: Wind, tile: MahjongTile, context: &WinContext) -> Option<HandScore> {
+        let player_state = self.player(seat);
+        let mut hand = *player_state.hand_counts();
+        hand.add(tile);
+        if self.ruleset.scoring == Scoring::Mcr {
+            // The other three of its kind already out on the table. A
+            // discard or robbed kan tile is out there itself.
+            let shown = self.players.iter()
+                .flat_map(|p| p.discards().iter().copied().chain(p.melds().iter().flat_map(|m| m.from_hand())))
+                .filter(|t| *t == tile)
+                .count();
+            let context = WinContext { last_of_kind: shown - !context.tsumo as usize == 3, ..*context };
+            let flowers = player_state.flowers().len() as u32;
+            return best_mcr_score(&hand, player_state.melds(), tile, &context, flowers, self.ruleset.minimum_han)
+                .map(HandScore::Mcr);
+        }
+        let score = best_score(&hand, player_state.melds(), tile, context, &self.ruleset)?;
+        let tiles: Vec<MahjongTile> = player_state.hand().iter().copied()
+            .chain(std::iter::once(tile))
+            .chain(player_state.melds().iter().flat_map(|m| m.tiles().iter().copied()))
+            .chain(player_state.nukidora().iter().copied())
+            .collect();
+        let dora = |indicators: &[MahjongTile]| indicators.iter().map(|t| self.tile_set.dora(*t)).collect::<Vec<_>>();
+        let ura_dora = match context.riichi || context.double_riichi {
+            true => dora(self.dead_wall.ura_dora_indicators()),
+            false => Vec::new(),
+        };
+        let counted = Dora::count(&tiles, &dora(self.dead_wall.dora_indicators()), &ura_dora);
+        Some(HandScore::Riichi(score.with_dora(Dora { nukidora: player_state.nukidora().len() as u32, ..counted })))
+    }
+
+    // Pays the winners from the discarder, or from everyone on a tsumo. The
+    // honba and riichi sticks go to the first winner after the discarder, and
+    // the counters keep going only while the dealer wins.
+    fn settle(&mut self, winners: &[Wind], discarder: Option<Wind>) {
+        for (position, winner) in winners.iter().copied().enumerate() {
+            let score = self.scores.iter()
+                .find(|(seat, _)| *seat == winner)
+                .map(|(_, score)| score.clone())
+                .expect("settling a hand nobody won");
+            let score = match score {
+                HandScore::Riichi(score) => score,
+                HandScore::Mcr(score) => {
+                    self.settle_mcr(winner, discarder, &score);
+                    continue;
+                }
+            };
+            let honba = if position == 0 { self.honba } else { 0 };
+            match discarder {
+                Some(discarder) => {
+                    self.transfer(Some(discarder), Some(winner), score.ron(), Reason::Ron);
+                    self.transfer(Some(discarder), Some(winner), honba * HONBA, Reason::Honba);
+                }
+                None => self.collect_tsumo(winner, honba, |dealer| score.tsumo_from(dealer), Reason::Tsumo),
+            }
+            let sticks = std::mem::take(&mut self.riichi_sticks);
+            self.transfer(None, Some(winner), sticks * RIICHI_STICK as u32, Reason::RiichiSticks);
+        }
+        self.honba = match winners.contains(&Wind::East) && self.ruleset.scoring == Scoring::Riichi {
+            true => self.honba + 1,
+            false => 0,
+        };
+    }
+
+    // Under MCR every other seat pays the base, and the discarder, or every
+    // seat on a self-drawn win, pays the fan on top. There are no counters.
+    fn settle_mcr(&mut self, winner: Wind, discarder: Option<Wind>, score: &McrScore) {
+        let mut payer = self.next_seat(winner);
+        while payer != winner {
+            match discarder {
+                Some(discarder) if discarder != payer => self.transfer(Some(payer), Some(winner), score.from_others(), Reason::Ron),
+                Some(_) => self.transfer(Some(payer), Some(winner), score.from_payer(), Reason::Ron),
+                None => self.transfer(Some(payer), Some(winner), score.from_payer(), Reason::Tsumo),
+            }
+            payer = self.next_seat(payer);
+        }
+    }
+
+    // Takes a tsumo from each other seat, `share` being what a payer owes
+    // depending on whether they're the dealer. Honba are 100 a payer. In
+    // sanma the missing North's share is lost or split between the payers.
+    fn collect_tsumo(&mut self, winner: Wind, honba: u32, share: impl Fn(bool) -> u32, reason: Reason) {
+        let split = match (self.ruleset.sanma, self.ruleset.sanma_tsumo) {
+            (true, SanmaTsumo::Split) => (share(false) / 2).div_ceil(100) * 100,
+            _ => 0,
+        };
+        let mut payer = self.next_seat(winner);
+        while payer != winner {
+            self.transfer(Some(payer), Some(winner), share(payer == Wind::East) + split, reason);
+            self.transfer(Some(payer), Some(winner), honba * HONBA / 3, Reason::Honba);
+            payer = self.next_seat(payer);
+        }
+    }
+
+    // The draws checked once a discard has gone by unclaimed: the same wind
+    // thrown by all four seats on the first go-around, a fourth riichi, and a
+    // fourth kan unless one player has made all of them. The first two need
+    // four players.
+    fn abortive_draw(&self) -> Option<AbortiveDraw> {
+        let first_discards: Vec<MahjongTile> = self.players.iter().filter_map(|p| p.discards().first().copied()).collect();
+        let four_players = self.players.len() == 4;
+        if self.ruleset.abortive_draws.suufon_renda && four_players
+            && self.players.iter().all(|p| p.discards().len() == 1 && p.melds().is_empty())
+            && matches!(first_discards[0], MahjongTile::Wind(_))
+            && first_discards.iter().all(|t| *t == first_discards[0]) {
+            return Some(AbortiveDraw::SuufonRenda);
+        }
+        if self.ruleset.abortive_draws.suucha_riichi && four_players && self.players.iter().all(|p| p.riichi().is_some()) {
+            return Some(AbortiveDraw::SuuchaRiichi);
+        }
+        let kans = |p: &PlayerState| p.melds().iter().filter(|m| matches!(m, Meld::Kong(_))).count();
+        if self.ruleset.abortive_draws.suukaikan
+            && self.players.iter().map(kans).sum::<usize>() == 4
+            && self.players.iter().filter(|p| kans(p) > 0).count() > 1 {
+            return Some(AbortiveDraw::Suukaikan);
+        }
+        None
+    }
+
+    // Ryuukyoku: nagashi mangan is paid if anyone has it, otherwise the seats
+    // that aren't tenpai pay the ones that are
+    fn exhaustive_draw(&mut self) -> Outcome {
+        let seats = self.ruleset.seats();
+        let tenpai: Vec<Wind> = seats.iter().copied().filter(|seat| self.player(*seat).is_tenpai()).collect();
+        // Nothing is paid for a draw under MCR
+        if self.ruleset.scoring == Scoring::Mcr {
+            return Outcome::ExhaustiveDraw { tenpai, nagashi: Vec::new() };
+        }
+        let nagashi: Vec<Wind> = seats.iter().copied().filter(|seat| self.player(*seat).is_nagashi()).collect();
+        for winner in nagashi.iter().copied() {
+            let mangan = |dealer: bool| Limit::Mangan.base_points() * if dealer || winner == Wind::East { 2 } else { 1 };
+            self.collect_tsumo(winner, 0, mangan, Reason::NagashiMangan);
+        }
+        if nagashi.is_empty() && !tenpai.is_empty() && tenpai.len() < seats.len() {
+            // Each seat that isn't tenpai pays each one that is an even share
+            let penalty = if self.ruleset.sanma { SANMA_NOTEN_PENALTY } else { NOTEN_PENALTY };
+            let points = penalty / (tenpai.len() * (seats.len() - tenpai.len())) as u32;
+            for payer in seats.iter().filter(|seat| !tenpai.contains(seat)) {
+                for receiver in &tenpai {
+                    self.transfer(Some(*payer), Some(*receiver), points, Reason::Noten);
+                }
+            }
+        }
+        self.honba += 1;
+        Outcome::ExhaustiveDraw { tenpai, nagashi }
+    }
+
+    // Moves points between seats, or to and from the table, and records it
+    fn transfer(&mut self, from: Option<Wind>, to: Option<Wind>, points: u32, reason: Reason) {
+        if let Some(from) = from {
+            self.player_mut(from).add_points(-(points as i32));
+        }
+        if let Some(to) = to {
+            self.player_mut(to).add_points(points as i32);
+        }
+        self.settlement.record(from, to, points, reason);
+    }
+
+    fn break_ippatsu(&mut self) {
+        self.players.iter_mut().for_each(|p| p.break_ippatsu());
+    }
+
+    // Offers `tile` to the other seats and settles the claims: ron beats pon
+    // and kan, which beat chi. Between two rons the first seat after the
+    // discarder takes it. Ron needs a yaku, and a claim the hand can't back up
+    // is a chombo. The last tile of the wall can only be taken for ron.
+    fn offer_tile(&mut self, discarder: Wind, tile: MahjongTile, offer: Offer) -> GameTurnState {
+        let melds = offer == Offer::Discard && self.wall.has_tiles();
+        let kan = melds && self.kans_allowed();
+        let mut claims = Vec::new();
+        let mut scores = Vec::new();
+        let mut seat = self.next_seat(discarder);
+        while seat != discarder {
+            let player_state = self.player(seat);
+            let open = melds && player_state.riichi().is_none();
+            let chi = !self.ruleset.sanma && seat == discarder.next();
+            let mut options = player_state.call_options(tile, self.ruleset.scoring, chi, open, kan);
+            if options.contains(&DiscardResponse::Ron) {
+                let context = WinContext {
+                    last_tile: offer == Offer::Discard && !self.wall.has_tiles(),
+                    chankan: offer == Offer::AddedKan,
+                    ..self.win_context(seat)
+                };
+                match self.winning_score(seat, tile, &context) {
+                    Some(score) => scores.push((seat, score)),
+                    None => options.retain(|o| *o != DiscardResponse::Ron),
+                }
+            }
+            let response = player_state.offer_discard(tile, &options, &self.table_view(seat));
+            if !options.contains(&response) {
+                return GameTurnState::Chombo(seat);
+            }
+            if response != DiscardResponse::Ron && player_state.wins_with(tile) {
+                self.player_mut(seat).pass_winning_tile();
+            }
+            claims.push((seat, response));
+            seat = self.next_seat(seat);
+        }
+
+        if self.ruleset.abortive_draws.sanchahou && claims.iter().filter(|(_, r)| *r == DiscardResponse::Ron).count() == 3 {
+            return GameTurnState::Abort(AbortiveDraw::Sanchahou);
+        }
+        // Without double ron the first claim after the discarder takes it, and
+        // with it the first two do
+        let rons: Vec<Wind> = claims.iter().filter(|(_, r)| *r == DiscardResponse::Ron).map(|(seat, _)| *seat).collect();
+        let rons: Vec<Wind> = rons.into_iter().take(if self.ruleset.double_ron { 2 } else { 1 }).collect();
+        if !rons.is_empty() {
+            self.scores = scores.into_iter().filter(|(seat, _)| rons.contains(seat)).collect();
+            return match rons[..] {
+                [winner] => GameTurnState::Ron { winner, discarder },
+                _ => GameTurnState::DoubleRon { winners: [rons[0], rons[1]], discarder },
+            };
+        }
+        let call = claims.iter()
+            .find(|(_, r)| matches!(r, DiscardResponse::Pon | DiscardResponse::Kan))
+            .or_else(|| claims.iter().find(|(_, r)| matches!(r, DiscardResponse::Chi(..))));
+        if let Some((caller, response)) = call.copied() {
+            self.player_mut(caller).call(tile, discarder, response);
+            self.player_mut(discarder).discard_called();
+            self.break_ippatsu();
+            self.current_turn = caller;
+            match response {
+                DiscardResponse::Kan => self.kan(),
+                _ => self.next_draw = NextDraw::Skip,
+            }
+        }
+        GameTurnState::None
+    }
+
+    fn progress_turn(&mut self) {
+        self.current_turn = self.next_seat(self.current_turn);
     }
 }
 
+// What a winning hand was worth, under whichever rules were played
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandScore {
+    Riichi(Score),
+    Mcr(McrScore),
+}
+
+impl fmt::Display for HandScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandScore::Riichi(score) => score.fmt(f),
+            HandScore::Mcr(score) => score.fmt(f),
+        }
+    }
+}
+
+// How a hand ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Tsumo(Wind),
+    Ron { winner: Wind, discarder: Wind },
+    // Winners in seat order from the discarder
+    DoubleRon { winners: [Wind; 2], discarder: Wind },
+    // The wall ran out, with the seats that were tenpai and any that made
+    // nagashi mangan
+    ExhaustiveDraw { tenpai: Vec<Wind>, nagashi: Vec<Wind> },
+    // Stopped early, with nothing paid
+    AbortiveDraw(AbortiveDraw),
+    // The hand is void and played again
+    Chombo(Wind),
+}
+
+impl Outcome {
+    // The dealer keeps the seat after winning or being tenpai at a draw, and
+    // a void hand is played again by the same dealer
+    pub fn renchan(&self) -> bool {
+        match self {
+            Outcome::Tsumo(winner) | Outcome::Ron { winner, .. } => *winner == Wind::East,
+            Outcome::DoubleRon { winners, .. } => winners.contains(&Wind::East),
+            Outcome::ExhaustiveDraw { tenpai, .. } => tenpai.contains(&Wind::East),
+            Outcome::AbortiveDraw(_) | Outcome::Chombo(_) => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbortiveDraw {
+    // Declared by the player holding nine kinds of terminal and honour
+    KyuushuKyuuhai(Wind),
+    // Four of the same wind discarded on the first go-around
+    SuufonRenda,
+    // All four players in riichi
+    SuuchaRiichi,
+    // Four kans by more than one player
+    Suukaikan,
+    // Three players calling ron on the same tile
+    Sanchahou,
+}
+
+#[derive(Debug, PartialEq, Eq)]
 enum GameTurnState {
     None,
     Chombo(Wind),
-    Win(Wind),
+    Tsumo(Wind),
+    Ron { winner: Wind, discarder: Wind },
+    DoubleRon { winners: [Wind; 2], discarder: Wind },
+    Abort(AbortiveDraw),
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use crate::mahjong::mcr::fan::Fan;
+    use crate::mahjong::player::player::Player;
+    use crate::mahjong::player_state::player_state::{Riichi, STARTING_POINTS};
+    use crate::mahjong::strategy::block_strategy::Called;
+    use crate::mahjong::table::table::Transfer;
+    use crate::mahjong::tile::hand_counts::HandCounts;
+    use crate::mahjong::tile::notation::parse_tiles;
+    use crate::mahjong::yaku::yaku::Yaku;
+
+    use super::*;
+
+    // Claims ron on everything, whatever it holds
+    struct AlwaysRon;
+
+    impl Player for AlwaysRon {
+        fn turn(&self, hand: &[MahjongTile], _table: &TableView) -> TurnState {
+            TurnState::Discard(hand[hand.len() - 1])
+        }
+
+        fn offer_discard(&self, _hand: &[MahjongTile], _tile: MahjongTile, _options: &[DiscardResponse], _table: &TableView) -> DiscardResponse {
+            DiscardResponse::Ron
+        }
+    }
+
+    // Declares tsumo on every draw
+    struct AlwaysTsumo;
+
+    impl Player for AlwaysTsumo {
+        fn turn(&self, _hand: &[MahjongTile], _table: &TableView) -> TurnState {
+            TurnState::Tsumo
+        }
+
+        fn offer_discard(&self, _hand: &[MahjongTile], _tile: MahjongTile, _options: &[DiscardResponse], _table: &TableView) -> DiscardResponse {
+            DiscardResponse::Pass
+        }
+    }
+
+    // Declares kyuushu kyuuhai on every draw
+    struct Aborter;
+
+    impl Player for Aborter {
+        fn turn(&self, _hand: &[MahjongTile], _table: &TableView) -> TurnState {
+            TurnState::KyuushuKyuuhai
+        }
+
+        fn offer_discard(&self, _hand: &[MahjongTile], _tile: MahjongTile, _options: &[DiscardResponse], _table: &TableView) -> DiscardResponse {
+            DiscardResponse::Pass
+        }
+    }
+
+    // Makes the last claim it's offered, so kan over pon and chi over passing
+    struct Greedy;
+
+    impl Player for Greedy {
+        fn turn(&self, hand: &[MahjongTile], _table: &TableView) -> TurnState {
+            TurnState::Discard(hand[hand.len() - 1])
+        }
+
+        fn offer_discard(&self, _hand: &[MahjongTile], _tile: MahjongTile, options: &[DiscardResponse], _table: &TableView) -> DiscardResponse {
+            *options.last().unwrap()
+        }
+    }
+
+    // Declares every kan it can, otherwise throws its last tile
+    struct Kanner;
+
+    impl Player for Kanner {
+        fn turn(&self, hand: &[MahjongTile], table: &TableView) -> TurnState {
+            let counts = HandCounts::from_tiles(hand);
+            if table.can_kan {
+                if let Some((index, _)) = counts.iter().find(|(_, count)| *count == 4) {
+                    return TurnState::ClosedKan(index.tile());
+                }
+                if let Some(pung) = table.melds.iter().find(|m| m.tiles().len() == 3 && counts.contains(m.tiles()[0])) {
+                    return TurnState::AddedKan(pung.tiles()[0]);
+                }
+            }
+            TurnState::Discard(hand[hand.len() - 1])
+        }
+
+        fn offer_discard(&self, _hand: &[MahjongTile], _tile: MahjongTile, _options: &[DiscardResponse], _table: &TableView) -> DiscardResponse {
+            DiscardResponse::Pass
+        }
+    }
+
+    // Declares riichi throwing whatever it draws, then either keeps throwing
+    // its draws or breaks the lock
+    struct Riichier {
+        tsumogiri: bool,
+    }
+
+    impl Player for Riichier {
+        fn turn(&self, hand: &[MahjongTile], table: &TableView) -> TurnState {
+            match (table.can_riichi, self.tsumogiri) {
+                (true, _) => TurnState::Riichi(hand[hand.len() - 1]),
+                (false, true) => TurnState::Discard(hand[hand.len() - 1]),
+                (false, false) => TurnState::Discard(hand[0]),
+            }
+        }
+
+        fn offer_discard(&self, _hand: &[MahjongTile], _tile: MahjongTile, _options: &[DiscardResponse], _table: &TableView) -> DiscardResponse {
+            DiscardResponse::Pass
+        }
+    }
+
+    // A game dealing the given hands, East first, with every other tile of the
+    // set left in the wall
+    fn scripted(hands: [&str; 4]) -> Game {
+        scripted_with(Ruleset { red_fives: [0, 0, 0], ..Ruleset::default() }, &hands)
+    }
+
+    fn scripted_with(ruleset: Ruleset, hands: &[&str]) -> Game {
+        let hands: Vec<Vec<MahjongTile>> = hands.iter().map(|h| parse_tiles(h).unwrap()).collect();
+        let mut order = Vec::new();
+        for (start, handful) in [(0, 4), (4, 4), (8, 4), (12, 1)] {
+            for hand in &hands {
+                order.extend_from_slice(&hand[start..start + handful]);
+            }
+        }
+        let mut rest = ruleset.tile_set().tiles();
+        for tile in &order {
+            let position = rest.iter().position(|t| t == tile).unwrap();
+            rest.remove(position);
+        }
+        order.extend(rest);
+        Game::with_wall(ruleset, Wall::from_tiles(order))
+    }
+
+    // East holds no North, and the first tile left in the wall is one
+    const SANMA_HANDS: [&str; 3] = ["11m99m123p456p78s1z", "19m123p456p789s1z2z", "19m123p456p789s2z3z"];
+
+    fn sanma() -> Ruleset {
+        Ruleset { red_fives: [0, 0, 0], ..Ruleset::sanma() }
+    }
+
+    fn discard(game: &mut Game, discarder: Wind, tile: &str) -> GameTurnState {
+        let tile = tile.parse().unwrap();
+        game.player_mut(discarder).discard(tile).unwrap();
+        game.handle_discard(discarder, tile)
+    }
+
+    fn seat(game: &mut Game, state: PlayerState, hand: &str) {
+        let wind = state.wind();
+        let player = game.player_mut(wind);
+        *player = state;
+        player.deal(&parse_tiles(hand).unwrap());
+    }
+
+    #[test]
+    fn deals_thirteen_tiles_to_each_seat() {
+        let game = Game::new(Ruleset::default());
+        for player in &game.players {
+            assert_eq!(player.hand().len(), 13);
+        }
+        assert_eq!(game.dead_wall().len(), 14);
+        assert_eq!(game.wall.remaining(), 70);
+    }
+
+    #[test]
+    fn sanma_seats_three() {
+        let mut game = Game::with_seed(Ruleset::sanma(), 1);
+        assert_eq!(game.players.len(), 3);
+        assert_eq!(game.wall.remaining(), 108 - 14 - 3 * 13);
+        for seat in [Wind::East, Wind::South, Wind::West, Wind::East] {
+            assert_eq!(game.current_turn, seat);
+            game.progress_turn();
+        }
+        assert_eq!(game.player(Wind::West).points(), 35000);
+    }
+
+    #[test]
+    fn seeded_games_repeat() {
+        let (a, b) = (Game::with_seed(Ruleset::default(), 7), Game::with_seed(Ruleset::default(), 7));
+        assert_eq!(a.seed(), Some(7));
+        for wind in Wind::iter() {
+            assert_eq!(a.player(wind).hand(), b.player(wind).hand());
+        }
+    }
+
+    #[test]
+    fn turns_go_counter_clockwise_from_the_dealer() {
+        let mut game = Game::new(Ruleset::default());
+        let wall = game.wall.remaining();
+        for (turn, seat) in [Wind::East, Wind::South, Wind::West, Wind::North].iter().enumerate() {
+            assert_eq!(game.current_turn, *seat);
+            if let GameTurnState::None = game.turn() {
+                assert_eq!(game.player(*seat).hand().len(), 13);
+                assert_eq!(game.player(*seat).discards().len(), 1);
+                assert_eq!(game.wall.remaining(), wall - turn - 1);
+            } else {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn ron_goes_to_the_first_claim_after_the_discarder() {
+        let mut game = Game::with_seed(Ruleset::wrc(), 1);
+        seat(&mut game, PlayerState::new(Wind::South), "123456789m23p11z");
+        seat(&mut game, PlayerState::new(Wind::West), "123456789s14p22z");
+        seat(&mut game, PlayerState::new(Wind::North), "111999m123s23p77z");
+        let one_pin = "1p".parse().unwrap();
+        assert_eq!(game.handle_discard(Wind::East, one_pin), GameTurnState::Ron { winner: Wind::South, discarder: Wind::East });
+        assert_eq!(game.handle_discard(Wind::South, one_pin), GameTurnState::Ron { winner: Wind::North, discarder: Wind::South });
+        assert_eq!(game.handle_discard(Wind::West, "5p".parse().unwrap()), GameTurnState::None);
+    }
+
+    #[test]
+    fn the_discarder_pays_for_a_ron() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::new(Wind::South), "123456789m23p11z");
+        assert_eq!(game.handle_discard(Wind::East, "1p".parse().unwrap()), GameTurnState::Ron { winner: Wind::South, discarder: Wind::East });
+        // Ittsu, with fu for the closed ron and the pair of the round wind
+        let HandScore::Riichi(score) = &game.scores()[0].1 else { panic!("scored under MCR") };
+        assert_eq!((score.han, score.fu), (2, 40));
+        // Ura-dora are for riichi hands only
+        assert_eq!(score.dora.ura_dora, 0);
+        game.settle(&[Wind::South], Some(Wind::East));
+        assert_eq!(game.settlement().transfers, vec![Transfer { from: Some(Wind::East), to: Some(Wind::South), points: 2600, reason: Reason::Ron }]);
+        assert_eq!(game.player(Wind::East).points(), STARTING_POINTS - 2600);
+        assert_eq!(game.player(Wind::South).points(), STARTING_POINTS + 2600);
+    }
+
+    #[test]
+    fn a_double_ron_pays_both_winners() {
+        let table = TableState { riichi_sticks: 1, honba: 1, ..TableState::new() };
+        let mut game = Game::with_table(Ruleset { red_fives: [0, 0, 0], ..Ruleset::default() }, Wall::with_seed(1), table);
+        seat(&mut game, PlayerState::new(Wind::South), "123456789m23p11z");
+        seat(&mut game, PlayerState::new(Wind::West), "123456789m23p11z");
+        let double = GameTurnState::DoubleRon { winners: [Wind::South, Wind::West], discarder: Wind::East };
+        assert_eq!(game.handle_discard(Wind::East, "1p".parse().unwrap()), double);
+        game.settle(&[Wind::South, Wind::West], Some(Wind::East));
+        // The honba and sticks only go to the first winner
+        assert_eq!(game.settlement().net(Wind::South), 3900);
+        assert_eq!(game.settlement().net(Wind::West), 2600);
+        assert_eq!(game.settlement().net(Wind::East), -5500);
+        assert_eq!(game.table().honba, 0);
+    }
+
+    #[test]
+    fn north_is_set_aside_for_a_replacement() {
+        let mut game = scripted_with(sanma(), &SANMA_HANDS);
+        assert_eq!(game.turn(), GameTurnState::None);
+        let east = game.player(Wind::East);
+        assert_eq!((east.nukidora().len(), east.hand().len()), (1, 13));
+        assert_eq!((game.current_turn, game.next_draw), (Wind::East, NextDraw::DeadWall));
+        assert_eq!(game.table_view(Wind::South).visible.count(MahjongTile::Wind(Wind::North)), 1);
+    }
+
+    #[test]
+    fn no_chi_in_sanma() {
+        let mut game = scripted_with(sanma(), &SANMA_HANDS);
+        seat(&mut game, PlayerState::with_player(Wind::South, Box::new(Greedy)), "23p99m123s456s789s1z");
+        assert_eq!(discard(&mut game, Wind::East, "1p"), GameTurnState::None);
+        assert!(game.player(Wind::South).melds().is_empty());
+    }
+
+    #[test]
+    fn flowers_are_set_aside_under_mcr() {
+        let mut game = Game::with_seed(Ruleset::mcr(), 1);
+        let flowers: usize = game.players.iter().map(|p| p.flowers().len()).sum();
+        assert!(flowers > 0);
+        assert!(game.players.iter().all(|p| p.hand().len() == 13 && !p.hand().iter().any(|t| t.is_flower())));
+        game.run();
+        let total: i32 = game.players.iter().map(|p| p.points()).sum();
+        assert_eq!((total, game.table().honba), (0, 0));
+    }
+
+    #[test]
+    fn mcr_pays_the_base_and_the_fan() {
+        let score = McrScore { fan: vec![Fan::MixedStraight], flowers: 1, total: 9, tsumo: false };
+        let mut game = Game::with_seed(Ruleset::mcr(), 1);
+        game.scores = vec![(Wind::South, HandScore::Mcr(score.clone()))];
+        game.settle(&[Wind::South], Some(Wind::East));
+        assert_eq!(game.settlement().net(Wind::South), 33);
+        assert_eq!((game.settlement().net(Wind::East), game.settlement().net(Wind::West)), (-17, -8));
+
+        let mut game = Game::with_seed(Ruleset::mcr(), 1);
+        game.scores = vec![(Wind::East, HandScore::Mcr(McrScore { tsumo: true, ..score }))];
+        game.settle(&[Wind::East], None);
+        assert_eq!(game.settlement().net(Wind::East), 51);
+        assert_eq!(game.table().honba, 0);
+    }
+
+    #[test]
+    fn sanma_tsumo_loss_or_split() {
+        let context = WinContext { tsumo: true, ..WinContext::new(Wind::South, Wind::East) };
+        let score = Score::new(vec![Yaku::Riichi, Yaku::MenzenTsumo], 30, true, &context);
+        let mut game = Game::with_seed(sanma(), 1);
+        game.scores = vec![(Wind::South, HandScore::Riichi(score.clone()))];
+        game.settle(&[Wind::South], None);
+        assert_eq!(game.settlement().net(Wind::South), 1500);
+
+        let mut game = Game::with_seed(Ruleset { sanma_tsumo: SanmaTsumo::Split, ..sanma() }, 1);
+        game.scores = vec![(Wind::South, HandScore::Riichi(score))];
+        game.settle(&[Wind::South], None);
+        assert_eq!(game.settlement().net(Wind::East), -1300);
+        assert_eq!(game.settlement().net(Wind::West), -800);
+    }
+
+    #[test]
+    fn false_ron_is_chombo() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::with_player(Wind::West, Box::new(AlwaysRon)), "19m19p19s1234567z");
+        assert_eq!(game.handle_discard(Wind::South, "5m".parse().unwrap()), GameTurnState::Chombo(Wind::West));
+        assert_eq!(game.handle_discard(Wind::South, "9s".parse().unwrap()), GameTurnState::Ron { winner: Wind::West, discarder: Wind::South });
+    }
+
+    #[test]
+    fn honba_and_riichi_sticks_go_to_the_winner() {
+        let table = TableState { riichi_sticks: 2, honba: 3, ..TableState::new() };
+        let mut game = Game::with_table(Ruleset { red_fives: [0, 0, 0], ..Ruleset::default() }, Wall::with_seed(1), table);
+        seat(&mut game, PlayerState::new(Wind::South), "123456789m23p11z");
+        game.handle_discard(Wind::East, "1p".parse().unwrap());
+        game.settle(&[Wind::South], Some(Wind::East));
+        let settlement = game.settlement();
+        assert_eq!(settlement.transfers[1], Transfer { from: Some(Wind::East), to: Some(Wind::South), points: 900, reason: Reason::Honba });
+        assert_eq!(settlement.transfers[2], Transfer { from: None, to: Some(Wind::South), points: 2000, reason: Reason::RiichiSticks });
+        assert_eq!(settlement.net(Wind::East), -3500);
+        assert_eq!(settlement.net(Wind::South), 5500);
+
+        // A non-dealer win clears the counters for the next hand
+        let next = game.table();
+        assert_eq!((next.riichi_sticks, next.honba), (0, 0));
+        assert_eq!(next.points(Wind::South), STARTING_POINTS + 5500);
+    }
+
+    #[test]
+    fn a_dealer_tsumo_splits_the_honba() {
+        let hands = ["123456789m55z77z", RIICHI[1], RIICHI[2], RIICHI[3]];
+        let mut game = scripted(hands);
+        game.honba = 1;
+        seat(&mut game, PlayerState::with_player(Wind::East, Box::new(AlwaysTsumo)), hands[0]);
+        game.turn();
+        game.settle(&[Wind::East], None);
+        assert_eq!(game.settlement().net(Wind::East), 48300);
+        assert_eq!(game.table().honba, 2);
+    }
+
+    #[test]
+    fn noten_seats_pay_the_tenpai_ones() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::new(Wind::East), "123456789m23p11z");
+        seat(&mut game, PlayerState::new(Wind::South), "147m258p369s1234z");
+        seat(&mut game, PlayerState::new(Wind::West), "147m258p369s1235z");
+        seat(&mut game, PlayerState::new(Wind::North), "123456789s23p11z");
+        let outcome = game.exhaustive_draw();
+        assert_eq!(outcome, Outcome::ExhaustiveDraw { tenpai: vec![Wind::East, Wind::North], nagashi: vec![] });
+        assert!(outcome.renchan());
+        assert_eq!(game.settlement().transfers.len(), 4);
+        assert_eq!(game.settlement().net(Wind::East), 1500);
+        assert_eq!(game.settlement().net(Wind::West), -1500);
+        assert_eq!(game.table().honba, 1);
+    }
+
+    #[test]
+    fn nagashi_mangan_is_paid_instead() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::new(Wind::East), "147m258p369s1235z");
+        seat(&mut game, PlayerState::new(Wind::South), "147m258p369s1234z9m");
+        seat(&mut game, PlayerState::new(Wind::West), "123456789s23p11z");
+        seat(&mut game, PlayerState::new(Wind::North), "147m258p369s1235z");
+        game.player_mut(Wind::South).discard("9m".parse().unwrap()).unwrap();
+        let outcome = game.exhaustive_draw();
+        assert_eq!(outcome, Outcome::ExhaustiveDraw { tenpai: vec![Wind::West], nagashi: vec![Wind::South] });
+        assert!(!outcome.renchan());
+        assert_eq!(game.settlement().net(Wind::South), 8000);
+        assert_eq!(game.settlement().net(Wind::East), -4000);
+        assert_eq!(game.settlement().net(Wind::West), -2000);
+    }
+
+    #[test]
+    fn a_called_discard_rules_out_nagashi() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::new(Wind::South), "147m258p369s1234z1z");
+        seat(&mut game, PlayerState::with_player(Wind::West, Box::new(Greedy)), "11z47m258p369s235z");
+        assert_eq!(discard(&mut game, Wind::South, "1z"), GameTurnState::None);
+        assert_eq!(game.player(Wind::West).melds().len(), 1);
+        assert!(!game.player(Wind::South).is_nagashi());
+    }
+
+    #[test]
+    fn nine_kinds_on_the_first_draw() {
+        let hands = ["19m19p19s1234z234m", RIICHI[1], RIICHI[2], RIICHI[3]];
+        let mut game = scripted(hands);
+        seat(&mut game, PlayerState::with_player(Wind::East, Box::new(Aborter)), hands[0]);
+        assert_eq!(game.turn(), GameTurnState::Abort(AbortiveDraw::KyuushuKyuuhai(Wind::East)));
+
+        let mut game = scripted(hands);
+        game.ruleset.abortive_draws.kyuushu_kyuuhai = false;
+        seat(&mut game, PlayerState::with_player(Wind::East, Box::new(Aborter)), hands[0]);
+        assert_eq!(game.turn(), GameTurnState::Chombo(Wind::East));
+    }
+
+    #[test]
+    fn four_winds_and_four_riichi() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        for wind in Wind::iter() {
+            seat(&mut game, PlayerState::new(wind), "147m258p369s1235z");
+        }
+        for wind in [Wind::East, Wind::South, Wind::West] {
+            discard(&mut game, wind, "1z");
+        }
+        assert_eq!(game.abortive_draw(), None);
+        discard(&mut game, Wind::North, "1z");
+        assert_eq!(game.abortive_draw(), Some(AbortiveDraw::SuufonRenda));
+
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        for wind in Wind::iter() {
+            seat(&mut game, PlayerState::new(wind), "123456789m23p11z9s");
+            game.player_mut(wind).declare_riichi("9s".parse().unwrap(), false).unwrap();
+        }
+        assert_eq!(game.abortive_draw(), Some(AbortiveDraw::SuuchaRiichi));
+        game.ruleset.abortive_draws.suucha_riichi = false;
+        assert_eq!(game.abortive_draw(), None);
+    }
+
+    #[test]
+    fn four_kans_by_more_than_one_player() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::new(Wind::East), "1111z2222z3333z5m");
+        seat(&mut game, PlayerState::new(Wind::South), "4444z123456789m");
+        for tile in ["1z", "2z", "3z"] {
+            game.player_mut(Wind::East).closed_kan(tile.parse().unwrap()).unwrap();
+        }
+        assert_eq!(game.abortive_draw(), None);
+        game.player_mut(Wind::South).closed_kan("4z".parse().unwrap()).unwrap();
+        assert_eq!(game.abortive_draw(), Some(AbortiveDraw::Suukaikan));
+    }
+
+    #[test]
+    fn three_rons_abort_the_hand() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        for wind in [Wind::South, Wind::West, Wind::North] {
+            seat(&mut game, PlayerState::new(wind), "123456789m23p11z");
+        }
+        assert_eq!(game.handle_discard(Wind::East, "1p".parse().unwrap()), GameTurnState::Abort(AbortiveDraw::Sanchahou));
+        game.ruleset.abortive_draws.sanchahou = false;
+        let double = GameTurnState::DoubleRon { winners: [Wind::South, Wind::West], discarder: Wind::East };
+        assert_eq!(game.handle_discard(Wind::East, "1p".parse().unwrap()), double);
+        game.ruleset.double_ron = false;
+        assert_eq!(game.handle_discard(Wind::East, "1p".parse().unwrap()), GameTurnState::Ron { winner: Wind::South, discarder: Wind::East });
+    }
+
+    #[test]
+    fn ron_needs_a_yaku() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::with_player(Wind::West, Box::new(AlwaysRon)), "234m456p789s11z55s");
+        assert_eq!(game.handle_discard(Wind::South, "5s".parse().unwrap()), GameTurnState::Chombo(Wind::West));
+
+        // Riichi is a yaku by itself
+        seat(&mut game, PlayerState::with_player(Wind::West, Box::new(AlwaysRon)), "234m456p789s11z55s9m");
+        game.player_mut(Wind::West).declare_riichi("9m".parse().unwrap(), false).unwrap();
+        assert_eq!(game.handle_discard(Wind::South, "5s".parse().unwrap()), GameTurnState::Ron { winner: Wind::West, discarder: Wind::South });
+    }
+
+    #[test]
+    fn tsumo_needs_a_complete_hand() {
+        // East's first draw is 7z, which wins nothing
+        let mut game = scripted(RIICHI);
+        seat(&mut game, PlayerState::with_player(Wind::East, Box::new(AlwaysTsumo)), RIICHI[0]);
+        assert_eq!(game.turn(), GameTurnState::Chombo(Wind::East));
+
+        // Waiting on 5z/7z instead, the first draw is 5z for tenhou
+        let hands = ["123456789m55z77z", RIICHI[1], RIICHI[2], RIICHI[3]];
+        let mut game = scripted(hands);
+        seat(&mut game, PlayerState::with_player(Wind::East, Box::new(AlwaysTsumo)), hands[0]);
+        assert_eq!(game.turn(), GameTurnState::Tsumo(Wind::East));
+        game.settle(&[Wind::East], None);
+        let paid: Vec<(Option<Wind>, u32)> = game.settlement().transfers.iter().map(|t| (t.from, t.points)).collect();
+        assert_eq!(paid, vec![(Some(Wind::South), 16000), (Some(Wind::West), 16000), (Some(Wind::North), 16000)]);
+        assert_eq!(game.player(Wind::East).points(), STARTING_POINTS + 48000);
+    }
+
+    const CALLS: [&str; 4] = ["5m123p456p789p111s", "46m234567s888s29s", "55m1122334455z6z", "2379m19p19s12347z"];
+
+    #[test]
+    fn pon_beats_chi_and_the_caller_discards_without_drawing() {
+        let mut game = scripted(CALLS);
+        seat(&mut game, PlayerState::with_player(Wind::South, Box::new(Greedy)), CALLS[1]);
+        seat(&mut game, PlayerState::with_player(Wind::West, Box::new(Greedy)), CALLS[2]);
+        assert_eq!(discard(&mut game, Wind::East, "5m"), GameTurnState::None);
+
+        let west = game.player(Wind::West);
+        assert_eq!(west.melds().len(), 1);
+        assert_eq!(west.melds()[0].called(), Some(Called { tile: "5m".parse().unwrap(), from: Wind::East }));
+        assert_eq!(west.hand().len(), 11);
+        assert!(game.player(Wind::South).melds().is_empty());
+        assert_eq!(game.current_turn, Wind::West);
+
+        let wall = game.wall.remaining();
+        assert_eq!(game.turn(), GameTurnState::None);
+        assert_eq!(game.wall.remaining(), wall);
+        assert_eq!(game.player(Wind::West).hand().len(), 10);
+        assert_eq!(game.current_turn, Wind::North);
+    }
+
+    #[test]
+    fn chi_only_from_the_seat_before() {
+        let mut game = scripted(CALLS);
+        seat(&mut game, PlayerState::with_player(Wind::South, Box::new(Greedy)), CALLS[1]);
+        assert_eq!(discard(&mut game, Wind::East, "5m"), GameTurnState::None);
+        assert_eq!(game.player(Wind::South).melds()[0].tiles(), parse_tiles("456m").unwrap());
+        assert_eq!(game.current_turn, Wind::South);
+
+        let options = game.player(Wind::South).call_options("5m".parse().unwrap(), Scoring::Riichi, false, true, true);
+        assert_eq!(options, vec![DiscardResponse::Pass]);
+    }
+
+    #[test]
+    fn open_kan_draws_a_replacement_and_flips_a_dora() {
+        let hands = ["5m123p456p789p111s", "46m234567s888s29s", "555m112233445z6z", "2379m19p19s12347z"];
+        let mut game = scripted(hands);
+        seat(&mut game, PlayerState::with_player(Wind::West, Box::new(Greedy)), hands[2]);
+        assert_eq!(discard(&mut game, Wind::East, "5m"), GameTurnState::None);
+        assert_eq!(game.player(Wind::West).melds()[0].tiles().len(), 4);
+        assert_eq!(game.dead_wall().dora_indicators().len(), 2);
+
+        let wall = game.wall.remaining();
+        assert_eq!(game.turn(), GameTurnState::None);
+        // The dead wall takes a tile from the live wall to stay at fourteen
+        assert_eq!(game.wall.remaining(), wall - 1);
+        assert_eq!(game.player(Wind::West).hand().len(), 10);
+    }
+
+    const KANS: [&str; 4] = ["1111z123p456p789p", "46m234567s888s22s", "555m19s2233z4455z", "2379m19p19s23477z"];
+
+    #[test]
+    fn closed_kan_keeps_the_turn_and_draws_from_the_dead_wall() {
+        let mut game = scripted(KANS);
+        seat(&mut game, PlayerState::with_player(Wind::East, Box::new(Kanner)), KANS[0]);
+        let wall = game.wall.remaining();
+        assert_eq!(game.turn(), GameTurnState::None);
+        let east = game.player(Wind::East);
+        assert_eq!(east.melds().len(), 1);
+        assert!(!east.melds()[0].is_open());
+        assert_eq!(east.hand().len(), 10);
+        assert_eq!(game.dead_wall().dora_indicators().len(), 2);
+        assert_eq!(game.current_turn, Wind::East);
+
+        assert_eq!(game.turn(), GameTurnState::None);
+        assert_eq!(game.wall.remaining(), wall - 2);
+        assert_eq!(game.player(Wind::East).hand().len(), 10);
+        assert_eq!(game.player(Wind::East).discards().len(), 1);
+        assert_eq!(game.current_turn, Wind::South);
+    }
+
+    #[test]
+    fn added_kan_can_be_robbed() {
+        let hands = ["1111z123p456p789p", "46m234567s888s22s", "555m19s2233z4455z", "2359m19p19s23477z"];
+        let mut game = scripted(hands);
+        seat(&mut game, PlayerState::with_player(Wind::West, Box::new(Kanner)), hands[2]);
+        let five = "5m".parse().unwrap();
+        assert_eq!(game.player_mut(Wind::West).added_kan(five), Err("No pung to add to"));
+
+        // West pons North's 5m and adds the one left in hand on its next turn,
+        // which South is waiting on
+        game.player_mut(Wind::North).discard(five).unwrap();
+        game.player_mut(Wind::West).call(five, Wind::North, DiscardResponse::Pon);
+        game.current_turn = Wind::West;
+        assert_eq!(game.turn(), GameTurnState::Ron { winner: Wind::South, discarder: Wind::West });
+    }
+
+    // East is waiting on 1p/4p
+    const RIICHI: [&str; 4] = ["123456789m23p55z", "46m234567s888s29s", "55m1122334466z6z", "2379m19p19s12347z"];
+
+    #[test]
+    fn riichi_puts_down_a_stick() {
+        let mut game = scripted(RIICHI);
+        seat(&mut game, PlayerState::with_player(Wind::East, Box::new(Riichier { tsumogiri: true })), RIICHI[0]);
+        assert_eq!(game.turn(), GameTurnState::None);
+        let east = game.player(Wind::East);
+        assert_eq!(east.riichi(), Some(Riichi { double: true, ippatsu: true }));
+        assert_eq!(east.points(), STARTING_POINTS - RIICHI_STICK);
+        assert_eq!(east.discards().len(), 1);
+        assert_eq!(game.riichi_sticks(), 1);
+        assert_eq!(game.settlement().transfers, vec![Transfer { from: Some(Wind::East), to: None, points: 1000, reason: Reason::RiichiDeposit }]);
+
+        // There is no declaring twice
+        assert_eq!(game.player_mut(Wind::East).declare_riichi("5z".parse().unwrap(), false), Err("Already in riichi"));
+    }
+
+    #[test]
+    fn riichi_needs_a_closed_tenpai_hand() {
+        let mut game = scripted(RIICHI);
+        let south = game.player_mut(Wind::South);
+        assert_eq!(south.declare_riichi("2s".parse().unwrap(), false), Err("Not tenpai"));
+        assert_eq!(south.riichi(), None);
+
+        let mut game = scripted(CALLS);
+        seat(&mut game, PlayerState::with_player(Wind::West, Box::new(Greedy)), CALLS[2]);
+        discard(&mut game, Wind::East, "5m");
+        assert_eq!(game.player_mut(Wind::West).declare_riichi("6z".parse().unwrap(), false), Err("Hand is open"));
+    }
+
+    #[test]
+    fn riichi_locks_the_hand() {
+        let mut game = scripted(RIICHI);
+        seat(&mut game, PlayerState::with_player(Wind::East, Box::new(Riichier { tsumogiri: false })), RIICHI[0]);
+        let mut outcome = GameTurnState::None;
+        for _ in 0..5 {
+            outcome = game.turn();
+            if outcome != GameTurnState::None {
+                break;
+            }
+        }
+        assert_eq!(outcome, GameTurnState::Chombo(Wind::East));
+    }
+
+    #[test]
+    fn waits_in_own_discards_are_furiten() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::new(Wind::South), "123456789m234p11z");
+        game.player_mut(Wind::South).discard("4p".parse().unwrap()).unwrap();
+        assert!(game.player(Wind::South).is_furiten());
+        assert!(game.table_view(Wind::South).is_furiten());
+        assert_eq!(game.handle_discard(Wind::East, "1p".parse().unwrap()), GameTurnState::None);
+
+        seat(&mut game, PlayerState::with_player(Wind::South, Box::new(AlwaysRon)), "123456789m234p11z");
+        game.player_mut(Wind::South).discard("4p".parse().unwrap()).unwrap();
+        assert_eq!(game.handle_discard(Wind::East, "1p".parse().unwrap()), GameTurnState::Chombo(Wind::South));
+    }
+
+    #[test]
+    fn passing_a_win_is_furiten_until_the_next_discard() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::with_player(Wind::South, Box::new(Kanner)), "123456789m23p11z");
+        let one_pin = "1p".parse().unwrap();
+        assert_eq!(game.handle_discard(Wind::East, one_pin), GameTurnState::None);
+        assert!(game.player(Wind::South).is_furiten());
+        assert_eq!(game.player(Wind::South).call_options("4p".parse().unwrap(), Scoring::Riichi, false, false, false), vec![DiscardResponse::Pass]);
+
+        let table = game.table_view(Wind::South);
+        let south = game.player_mut(Wind::South);
+        let TurnState::Discard(tile) = south.turn("9s".parse().unwrap(), &table) else { panic!() };
+        south.discard(tile).unwrap();
+        assert!(!game.player(Wind::South).is_furiten());
+    }
+
+    #[test]
+    fn mcr_offers_ron_to_a_furiten_hand() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::with_player(Wind::South, Box::new(Kanner)), "123456789m23p11z");
+        assert_eq!(game.handle_discard(Wind::East, "1p".parse().unwrap()), GameTurnState::None);
+        let south = game.player(Wind::South);
+        assert!(south.is_furiten());
+        let options = south.call_options("4p".parse().unwrap(), Scoring::Mcr, false, false, false);
+        assert_eq!(options, vec![DiscardResponse::Pass, DiscardResponse::Ron]);
+    }
+
+    #[test]
+    fn passing_a_win_in_riichi_is_furiten_for_the_hand() {
+        let mut game = Game::with_seed(Ruleset::default(), 1);
+        seat(&mut game, PlayerState::with_player(Wind::South, Box::new(Kanner)), "123456789m23p11z9s");
+        game.player_mut(Wind::South).declare_riichi("9s".parse().unwrap(), false).unwrap();
+        assert!(!game.player(Wind::South).is_furiten());
+        game.handle_discard(Wind::East, "4p".parse().unwrap());
+
+        let table = game.table_view(Wind::South);
+        let south = game.player_mut(Wind::South);
+        south.turn("9s".parse().unwrap(), &table);
+        south.discard("9s".parse().unwrap()).unwrap();
+        assert!(south.is_furiten());
+    }
 }
@@ -0,0 +1,303 @@
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+
+use super::game::{Game, Outcome};
+use super::ruleset::ruleset::{Ruleset, Scoring};
+use super::table::table::TableState;
+use super::tile::{enums::Wind, mahjong_tile::Nextable};
+use super::wall::wall::Wall;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchLength {
+    // The East round alone
+    Tonpuusen,
+    // East then South
+    Hanchan,
+    // All four winds
+    Iichan,
+}
+
+impl MatchLength {
+    fn last_round(&self) -> Wind {
+        match self {
+            MatchLength::Tonpuusen => Wind::East,
+            MatchLength::Hanchan => Wind::South,
+            MatchLength::Iichan => Wind::North,
+        }
+    }
+}
+
+// A run of hands with the same three or four players. Players are numbered
+// in seat order from the first dealer, and the dealer sits East.
+pub struct Match {
+    // Agari-yame lets the dealer end the match by winning or being tenpai in
+    // the last hand while in first place. The west extension plays one more
+    // round when nobody reached the target.
+    ruleset: Ruleset,
+    seed: u64,
+    rng: StdRng,
+    points: Vec<i32>,
+    round: Wind,
+    dealer: usize,
+    riichi_sticks: u32,
+    honba: u32,
+    hands: u32,
+    // Playing the round after the last, which can't be told from the wind
+    // once an iichan wraps round to East
+    extra_round: bool,
+    over: bool,
+}
+
+impl Match {
+    pub fn new(ruleset: Ruleset) -> Self {
+        Self::with_seed(ruleset, thread_rng().gen())
+    }
+
+    // Every hand of the match is dealt from `seed`
+    pub fn with_seed(ruleset: Ruleset, seed: u64) -> Self {
+        Match {
+            ruleset,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            points: vec![ruleset.starting_points; ruleset.players()],
+            round: Wind::East,
+            dealer: 0,
+            riichi_sticks: 0,
+            honba: 0,
+            hands: 0,
+            extra_round: false,
+            over: false,
+        }
+    }
+
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    // Pass back to `with_seed` to replay the match
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn points(&self) -> &[i32] {
+        &self.points
+    }
+
+    pub fn round(&self) -> Wind {
+        self.round
+    }
+
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    pub fn honba(&self) -> u32 {
+        self.honba
+    }
+
+    pub fn riichi_sticks(&self) -> u32 {
+        self.riichi_sticks
+    }
+
+    // Hands played so far, void ones included
+    pub fn hands(&self) -> u32 {
+        self.hands
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    // Where `player` sits this hand
+    pub fn seat(&self, player: usize) -> Wind {
+        let players = self.points.len();
+        (0..(player + players - self.dealer) % players).fold(Wind::East, |seat, _| seat.next())
+    }
+
+    // Plays the match out, returning each player's final points
+    pub fn run(&mut self) -> &[i32] {
+        while !self.over {
+            self.play_hand();
+        }
+        &self.points
+    }
+
+    // Deals and plays the next hand, then moves the dealer and round on
+    pub fn play_hand(&mut self) -> Outcome {
+        let table = TableState {
+            round_wind: self.round,
+            points: (0..self.points.len()).map(|player| (self.seat(player), self.points[player])).collect(),
+            riichi_sticks: self.riichi_sticks,
+            honba: self.honba,
+        };
+        let wall = Wall::from_tile_set_with_seed(&self.ruleset.tile_set(), self.rng.gen());
+        let mut game = Game::with_table(self.ruleset, wall, table);
+        let outcome = game.run();
+        self.hands += 1;
+        // Nothing a void hand did stands
+        if let Outcome::Chombo(_) = outcome {
+            return outcome;
+        }
+        let table = game.table();
+        for player in 0..self.points.len() {
+            self.points[player] = table.points(self.seat(player));
+        }
+        self.riichi_sticks = table.riichi_sticks;
+        self.honba = table.honba;
+        // Under MCR the deal always moves on
+        self.advance(outcome.renchan() && self.ruleset.scoring == Scoring::Riichi);
+        outcome
+    }
+
+    // The match ends when someone goes below zero under tobi, or once the last round is
+    // over with someone on the target. Past that, in the extra round, the
+    // first hand to leave someone on the target ends it.
+    fn advance(&mut self, renchan: bool) {
+        let rules = &self.ruleset;
+        let last = round_number(rules.length.last_round());
+        let top = *self.points.iter().max().unwrap();
+        let target = rules.target_points;
+        let players = self.points.len();
+        let all_last = round_number(self.round) == last && self.dealer == players - 1;
+        if (rules.tobi && self.points.iter().any(|points| *points < 0))
+            || (self.extra_round && top >= target)
+            || (all_last && renchan && rules.agari_yame && self.points[self.dealer] == top && top >= target) {
+            self.over = true;
+            return;
+        }
+        if renchan {
+            return;
+        }
+        self.dealer = (self.dealer + 1) % players;
+        if self.dealer == 0 {
+            let past_last = self.extra_round || round_number(self.round) == last;
+            self.round = self.round.next();
+            if past_last {
+                self.over = top >= target || !rules.west_extension || self.extra_round;
+                self.extra_round = true;
+            }
+        }
+    }
+}
+
+fn round_number(round: Wind) -> usize {
+    match round {
+        Wind::East => 0,
+        Wind::South => 1,
+        Wind::West => 2,
+        Wind::North => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plain rules, with the match options switched on by each test
+    fn rules(length: MatchLength) -> Ruleset {
+        Ruleset { length, agari_yame: false, west_extension: false, ..Ruleset::default() }
+    }
+
+    fn rotate(game_match: &mut Match, hands: usize) {
+        (0..hands).for_each(|_| game_match.advance(false));
+    }
+
+    #[test]
+    fn the_dealer_moves_round_the_table() {
+        let mut game_match = Match::with_seed(rules(MatchLength::Hanchan), 1);
+        assert_eq!(game_match.seat(0), Wind::East);
+        game_match.advance(true);
+        assert_eq!(game_match.dealer(), 0);
+        rotate(&mut game_match, 1);
+        assert_eq!((game_match.dealer(), game_match.seat(1), game_match.seat(0)), (1, Wind::East, Wind::North));
+        rotate(&mut game_match, 3);
+        assert_eq!((game_match.round(), game_match.dealer()), (Wind::South, 0));
+        assert!(!game_match.is_over());
+        rotate(&mut game_match, 4);
+        assert!(game_match.is_over());
+    }
+
+    #[test]
+    fn extension_until_someone_reaches_the_target() {
+        let mut game_match = Match::with_seed(Ruleset { west_extension: true, ..rules(MatchLength::Tonpuusen) }, 1);
+        rotate(&mut game_match, 4);
+        assert_eq!(game_match.round(), Wind::South);
+        assert!(!game_match.is_over());
+        game_match.points = vec![31000, 23000, 23000, 23000];
+        game_match.advance(true);
+        assert!(game_match.is_over());
+
+        let mut game_match = Match::with_seed(rules(MatchLength::Tonpuusen), 1);
+        game_match.points = vec![31000, 23000, 23000, 23000];
+        rotate(&mut game_match, 3);
+        assert!(!game_match.is_over());
+        game_match.advance(true);
+        assert!(!game_match.is_over());
+        rotate(&mut game_match, 1);
+        assert!(game_match.is_over());
+    }
+
+    #[test]
+    fn iichan_extension_wraps_round_to_east() {
+        let mut game_match = Match::with_seed(Ruleset { west_extension: true, ..rules(MatchLength::Iichan) }, 1);
+        rotate(&mut game_match, 16);
+        assert_eq!(game_match.round(), Wind::East);
+        assert!(!game_match.is_over());
+        game_match.points = vec![31000, 23000, 23000, 23000];
+        game_match.advance(true);
+        assert!(game_match.is_over());
+
+        let mut game_match = Match::with_seed(Ruleset { west_extension: true, ..rules(MatchLength::Iichan) }, 1);
+        rotate(&mut game_match, 19);
+        assert!(!game_match.is_over());
+        rotate(&mut game_match, 1);
+        assert!(game_match.is_over());
+    }
+
+    #[test]
+    fn agari_yame_and_tobi() {
+        let mut game_match = Match::with_seed(Ruleset { agari_yame: true, ..rules(MatchLength::Tonpuusen) }, 1);
+        rotate(&mut game_match, 3);
+        game_match.points = vec![23000, 23000, 23000, 31000];
+        game_match.advance(true);
+        assert!(game_match.is_over());
+
+        let mut game_match = Match::with_seed(rules(MatchLength::Hanchan), 1);
+        game_match.points = vec![-100, 41000, 34100, 25000];
+        game_match.advance(true);
+        assert!(game_match.is_over());
+
+        let mut game_match = Match::with_seed(Ruleset::wrc(), 1);
+        game_match.points = vec![-100, 41000, 34100, 25000];
+        game_match.advance(true);
+        assert!(!game_match.is_over());
+    }
+
+    #[test]
+    fn mcr_deals_each_seat_once_a_round() {
+        let mut game_match = Match::with_seed(Ruleset::mcr(), 1);
+        rotate(&mut game_match, 15);
+        assert_eq!((game_match.round(), game_match.dealer()), (Wind::North, 3));
+        assert!(!game_match.is_over());
+        rotate(&mut game_match, 1);
+        assert!(game_match.is_over());
+    }
+
+    #[test]
+    fn seeded_matches_play_out() {
+        let mut game_match = Match::with_seed(rules(MatchLength::Tonpuusen), 3);
+        game_match.run();
+        assert!(game_match.hands() >= 4);
+        let total: i32 = game_match.points().iter().sum();
+        assert_eq!(total + 1000 * game_match.riichi_sticks() as i32, 4 * game_match.ruleset().starting_points);
+    }
+
+    #[test]
+    fn sanma_matches_play_out() {
+        let mut game_match = Match::with_seed(Ruleset { length: MatchLength::Tonpuusen, ..Ruleset::sanma() }, 3);
+        assert_eq!(game_match.seat(2), Wind::West);
+        game_match.run();
+        assert!(game_match.hands() >= 3);
+        let total: i32 = game_match.points().iter().sum();
+        assert_eq!(total + 1000 * game_match.riichi_sticks() as i32, 3 * 35000);
+    }
+}
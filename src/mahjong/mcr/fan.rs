@@ -0,0 +1,733 @@
+use crate::mahjong::analysis::agari::Decomposition;
+use crate::mahjong::analysis::machi::WaitKind;
+use crate::mahjong::strategy::block_strategy::Meld;
+use crate::mahjong::tile::{enums::{Dragon, Suit}, hand_counts::HandCounts, mahjong_tile::MahjongTile, suited_tile::SuitedTile, tile_index::TileIndex};
+use crate::mahjong::yaku::{context::WinContext, yaku::{readings, Set, SetKind, Shape}};
+
+// The 81 fan of the Chinese Official rules, from most to least valuable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Fan {
+    // 88
+    BigFourWinds,
+    BigThreeDragons,
+    AllGreen,
+    NineGates,
+    FourKongs,
+    SevenShiftedPairs,
+    ThirteenOrphans,
+    // 64
+    AllTerminals,
+    LittleFourWinds,
+    LittleThreeDragons,
+    AllHonors,
+    FourConcealedPungs,
+    PureTerminalChows,
+    // 48
+    QuadrupleChow,
+    FourPureShiftedPungs,
+    // 32
+    FourPureShiftedChows,
+    ThreeKongs,
+    AllTerminalsAndHonors,
+    // 24
+    SevenPairs,
+    GreaterHonorsAndKnittedTiles,
+    AllEvenPungs,
+    FullFlush,
+    PureTripleChow,
+    PureShiftedPungs,
+    UpperTiles,
+    MiddleTiles,
+    LowerTiles,
+    // 16
+    PureStraight,
+    ThreeSuitedTerminalChows,
+    PureShiftedChows,
+    AllFives,
+    TriplePung,
+    ThreeConcealedPungs,
+    // 12
+    LesserHonorsAndKnittedTiles,
+    KnittedStraight,
+    UpperFour,
+    LowerFour,
+    BigThreeWinds,
+    // 8
+    MixedStraight,
+    ReversibleTiles,
+    MixedTripleChow,
+    MixedShiftedPungs,
+    ChickenHand,
+    LastTileDraw,
+    LastTileClaim,
+    OutWithReplacementTile,
+    RobbingTheKong,
+    TwoConcealedKongs,
+    // 6
+    AllPungs,
+    HalfFlush,
+    MixedShiftedChows,
+    AllTypes,
+    MeldedHand,
+    TwoDragonPungs,
+    // 4
+    OutsideHand,
+    FullyConcealedHand,
+    TwoMeldedKongs,
+    LastTile,
+    // 2
+    DragonPung,
+    PrevalentWind,
+    SeatWind,
+    ConcealedHand,
+    AllChows,
+    TileHog,
+    DoublePung,
+    TwoConcealedPungs,
+    ConcealedKong,
+    AllSimples,
+    // 1
+    PureDoubleChow,
+    MixedDoubleChow,
+    ShortStraight,
+    TwoTerminalChows,
+    PungOfTerminalsOrHonors,
+    MeldedKong,
+    OneVoidedSuit,
+    NoHonors,
+    EdgeWait,
+    ClosedWait,
+    SingleWait,
+    SelfDrawn,
+    FlowerTiles,
+}
+
+impl Fan {
+    pub fn points(&self) -> u32 {
+        match self {
+            Fan::BigFourWinds | Fan::BigThreeDragons | Fan::AllGreen | Fan::NineGates | Fan::FourKongs
+                | Fan::SevenShiftedPairs | Fan::ThirteenOrphans => 88,
+            Fan::AllTerminals | Fan::LittleFourWinds | Fan::LittleThreeDragons | Fan::AllHonors
+                | Fan::FourConcealedPungs | Fan::PureTerminalChows => 64,
+            Fan::QuadrupleChow | Fan::FourPureShiftedPungs => 48,
+            Fan::FourPureShiftedChows | Fan::ThreeKongs | Fan::AllTerminalsAndHonors => 32,
+            Fan::SevenPairs | Fan::GreaterHonorsAndKnittedTiles | Fan::AllEvenPungs | Fan::FullFlush
+                | Fan::PureTripleChow | Fan::PureShiftedPungs | Fan::UpperTiles | Fan::MiddleTiles
+                | Fan::LowerTiles => 24,
+            Fan::PureStraight | Fan::ThreeSuitedTerminalChows | Fan::PureShiftedChows | Fan::AllFives
+                | Fan::TriplePung | Fan::ThreeConcealedPungs => 16,
+            Fan::LesserHonorsAndKnittedTiles | Fan::KnittedStraight | Fan::UpperFour | Fan::LowerFour
+                | Fan::BigThreeWinds => 12,
+            Fan::MixedStraight | Fan::ReversibleTiles | Fan::MixedTripleChow | Fan::MixedShiftedPungs
+                | Fan::ChickenHand | Fan::LastTileDraw | Fan::LastTileClaim | Fan::OutWithReplacementTile
+                | Fan::RobbingTheKong | Fan::TwoConcealedKongs => 8,
+            Fan::AllPungs | Fan::HalfFlush | Fan::MixedShiftedChows | Fan::AllTypes | Fan::MeldedHand
+                | Fan::TwoDragonPungs => 6,
+            Fan::OutsideHand | Fan::FullyConcealedHand | Fan::TwoMeldedKongs | Fan::LastTile => 4,
+            Fan::DragonPung | Fan::PrevalentWind | Fan::SeatWind | Fan::ConcealedHand | Fan::AllChows
+                | Fan::TileHog | Fan::DoublePung | Fan::TwoConcealedPungs | Fan::ConcealedKong
+                | Fan::AllSimples => 2,
+            Fan::PureDoubleChow | Fan::MixedDoubleChow | Fan::ShortStraight | Fan::TwoTerminalChows
+                | Fan::PungOfTerminalsOrHonors | Fan::MeldedKong | Fan::OneVoidedSuit | Fan::NoHonors
+                | Fan::EdgeWait | Fan::ClosedWait | Fan::SingleWait | Fan::SelfDrawn | Fan::FlowerTiles => 1,
+        }
+    }
+
+    // Fan already part of this one, which aren't counted alongside it
+    fn implies(&self) -> &'static [Fan] {
+        match self {
+            Fan::BigFourWinds => &[Fan::LittleFourWinds, Fan::BigThreeWinds, Fan::AllPungs, Fan::SeatWind, Fan::PrevalentWind,
+                Fan::PungOfTerminalsOrHonors],
+            Fan::BigThreeDragons => &[Fan::LittleThreeDragons, Fan::TwoDragonPungs, Fan::DragonPung],
+            Fan::AllGreen => &[Fan::HalfFlush],
+            Fan::NineGates => &[Fan::FullFlush, Fan::ConcealedHand, Fan::PungOfTerminalsOrHonors, Fan::NoHonors],
+            Fan::FourKongs => &[Fan::ThreeKongs, Fan::TwoConcealedKongs, Fan::TwoMeldedKongs, Fan::ConcealedKong,
+                Fan::MeldedKong, Fan::AllPungs, Fan::SingleWait],
+            Fan::SevenShiftedPairs => &[Fan::SevenPairs, Fan::FullFlush, Fan::ConcealedHand, Fan::SingleWait, Fan::NoHonors],
+            Fan::ThirteenOrphans => &[Fan::AllTerminalsAndHonors, Fan::AllTypes, Fan::ConcealedHand, Fan::SingleWait],
+            Fan::AllTerminals => &[Fan::AllTerminalsAndHonors, Fan::AllPungs, Fan::OutsideHand, Fan::PungOfTerminalsOrHonors,
+                Fan::DoublePung, Fan::NoHonors],
+            Fan::LittleFourWinds => &[Fan::BigThreeWinds, Fan::PungOfTerminalsOrHonors],
+            Fan::LittleThreeDragons => &[Fan::TwoDragonPungs, Fan::DragonPung],
+            Fan::AllHonors => &[Fan::AllTerminalsAndHonors, Fan::AllPungs, Fan::OutsideHand, Fan::PungOfTerminalsOrHonors],
+            Fan::FourConcealedPungs => &[Fan::ThreeConcealedPungs, Fan::TwoConcealedPungs, Fan::AllPungs, Fan::ConcealedHand],
+            Fan::PureTerminalChows => &[Fan::FullFlush, Fan::AllChows, Fan::PureDoubleChow, Fan::TwoTerminalChows, Fan::NoHonors],
+            Fan::QuadrupleChow => &[Fan::PureTripleChow, Fan::PureShiftedPungs, Fan::PureDoubleChow, Fan::TileHog],
+            Fan::FourPureShiftedPungs => &[Fan::PureShiftedPungs, Fan::PureTripleChow, Fan::AllPungs],
+            Fan::FourPureShiftedChows => &[Fan::PureShiftedChows, Fan::ShortStraight, Fan::TwoTerminalChows],
+            Fan::ThreeKongs => &[Fan::TwoConcealedKongs, Fan::TwoMeldedKongs, Fan::ConcealedKong, Fan::MeldedKong],
+            Fan::AllTerminalsAndHonors => &[Fan::AllPungs, Fan::OutsideHand, Fan::PungOfTerminalsOrHonors],
+            Fan::SevenPairs => &[Fan::ConcealedHand, Fan::SingleWait],
+            Fan::GreaterHonorsAndKnittedTiles => &[Fan::LesserHonorsAndKnittedTiles, Fan::AllTypes, Fan::ConcealedHand],
+            Fan::AllEvenPungs => &[Fan::AllPungs, Fan::AllSimples, Fan::NoHonors],
+            Fan::FullFlush => &[Fan::NoHonors],
+            Fan::PureTripleChow => &[Fan::PureShiftedPungs, Fan::PureDoubleChow],
+            Fan::PureShiftedPungs => &[Fan::PureTripleChow],
+            Fan::UpperTiles => &[Fan::UpperFour, Fan::NoHonors],
+            Fan::MiddleTiles => &[Fan::AllSimples, Fan::NoHonors],
+            Fan::LowerTiles => &[Fan::LowerFour, Fan::NoHonors],
+            Fan::PureStraight => &[Fan::ShortStraight, Fan::TwoTerminalChows],
+            Fan::ThreeSuitedTerminalChows => &[Fan::AllChows, Fan::MixedDoubleChow, Fan::TwoTerminalChows, Fan::NoHonors],
+            Fan::AllFives => &[Fan::AllSimples, Fan::NoHonors],
+            Fan::TriplePung => &[Fan::DoublePung],
+            Fan::ThreeConcealedPungs => &[Fan::TwoConcealedPungs],
+            Fan::LesserHonorsAndKnittedTiles => &[Fan::AllTypes, Fan::ConcealedHand],
+            Fan::UpperFour | Fan::LowerFour => &[Fan::NoHonors],
+            Fan::ReversibleTiles => &[Fan::OneVoidedSuit],
+            Fan::MixedTripleChow => &[Fan::MixedDoubleChow],
+            Fan::LastTileDraw | Fan::OutWithReplacementTile => &[Fan::SelfDrawn],
+            Fan::RobbingTheKong => &[Fan::LastTile],
+            Fan::TwoConcealedKongs => &[Fan::ConcealedKong, Fan::TwoConcealedPungs],
+            Fan::MeldedHand => &[Fan::SingleWait],
+            Fan::TwoDragonPungs => &[Fan::DragonPung],
+            Fan::FullyConcealedHand => &[Fan::ConcealedHand, Fan::SelfDrawn],
+            Fan::TwoMeldedKongs => &[Fan::MeldedKong],
+            Fan::AllChows | Fan::AllSimples => &[Fan::NoHonors],
+            _ => &[],
+        }
+    }
+}
+
+pub fn total_fan(fan: &[Fan]) -> u32 {
+    fan.iter().map(|f| f.points()).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Form {
+    // Four sets and a pair
+    Standard,
+    SevenPairs,
+    ThirteenOrphans,
+    // 147, 258 and 369 each in a different suit, with a set and a pair
+    KnittedStraight,
+    // Fourteen odd tiles: knitted suits and single honours
+    HonorsAndKnitted,
+}
+
+// A winning hand as MCR reads it, which takes in the knitted hands as well
+// as the usual shapes
+pub(super) struct Reading {
+    form: Form,
+    shape: Shape,
+    // How the winning tile finished the hand, for the usual shapes
+    wait: Option<WaitKind>,
+    // No set was called off another player's discard
+    closed: bool,
+}
+
+// Every reading of a hand won on `tile`, with `concealed` already holding it
+pub(super) fn mcr_readings(concealed: &HandCounts, called: &[Meld], tile: MahjongTile, context: &WinContext) -> Vec<Reading> {
+    let closed = called.iter().all(|m| !m.is_open());
+    let mut found: Vec<Reading> = readings(concealed, called, tile).iter()
+        .map(|wait| {
+            let form = match wait.decomposition {
+                Decomposition::Standard { .. } => Form::Standard,
+                Decomposition::SevenPairs(_) => Form::SevenPairs,
+                Decomposition::ThirteenOrphans { .. } => Form::ThirteenOrphans,
+            };
+            Reading { form, shape: Shape::new(wait, context), wait: Some(wait.kind), closed }
+        })
+        .collect();
+    found.extend(knitted_straights(concealed, called, closed));
+    found.extend(honors_and_knitted(concealed, called));
+    found
+}
+
+// The three suits in every order, taking 147, 258 and 369 in turn
+const KNITS: [[Suit; 3]; 6] = [
+    [Suit::Man, Suit::Pin, Suit::Sou],
+    [Suit::Man, Suit::Sou, Suit::Pin],
+    [Suit::Pin, Suit::Man, Suit::Sou],
+    [Suit::Pin, Suit::Sou, Suit::Man],
+    [Suit::Sou, Suit::Man, Suit::Pin],
+    [Suit::Sou, Suit::Pin, Suit::Man],
+];
+
+fn knitted(suits: [Suit; 3]) -> Vec<TileIndex> {
+    suits.iter().enumerate()
+        .flat_map(|(start, suit)| (0..3).map(move |step| TileIndex::from(SuitedTile::new(*suit, start as i8 + 1 + 3 * step))))
+        .collect()
+}
+
+fn knitted_straights(concealed: &HandCounts, called: &[Meld], closed: bool) -> Vec<Reading> {
+    let mut found = Vec::new();
+    for suits in KNITS {
+        let straight = knitted(suits);
+        let mut rest = *concealed;
+        if !straight.iter().all(|t| rest.remove(*t)) {
+            continue;
+        }
+        // What's left is a pair with one set, called or still in hand
+        let mut endings: Vec<(Set, TileIndex)> = Vec::new();
+        match called {
+            [meld] if rest.len() == 2 => {
+                if let Some((pair, 2)) = rest.iter().next() {
+                    endings.push((Set::new(meld, !meld.is_open()), pair));
+                }
+            }
+            [] if rest.len() == 5 => {
+                for (pair, count) in rest.iter() {
+                    let mut set = rest;
+                    if count < 2 || !set.remove(pair) || !set.remove(pair) {
+                        continue;
+                    }
+                    let (lowest, count) = set.iter().next().unwrap();
+                    if count == 3 {
+                        endings.push((Set { kind: SetKind::Pung, tile: lowest, concealed: true }, pair));
+                    }
+                    let run = [lowest.offset(1), lowest.offset(2)];
+                    if run.iter().all(|t| t.is_some_and(|t| set.contains(t))) {
+                        endings.push((Set { kind: SetKind::Chow, tile: lowest, concealed: true }, pair));
+                    }
+                }
+            }
+            _ => (),
+        }
+        for (set, pair) in endings {
+            let mut tiles = straight.clone();
+            tiles.extend(set.tiles());
+            tiles.extend([pair, pair]);
+            let shape = Shape { sets: vec![set], pair: Some(pair), tiles };
+            found.push(Reading { form: Form::KnittedStraight, shape, wait: None, closed });
+        }
+    }
+    found
+}
+
+// Fourteen different tiles, each suit keeping to one of 147, 258 and 369
+fn honors_and_knitted(concealed: &HandCounts, called: &[Meld]) -> Option<Reading> {
+    if !called.is_empty() || concealed.len() != 14 || concealed.iter().any(|(_, count)| count > 1) {
+        return None;
+    }
+    let tiles: Vec<TileIndex> = concealed.iter().map(|(index, _)| index).collect();
+    let fits = KNITS.iter().any(|suits| {
+        let knitted = knitted(*suits);
+        tiles.iter().all(|t| t.is_honour() || knitted.contains(t))
+    });
+    match fits {
+        true => Some(Reading {
+            form: Form::HonorsAndKnitted,
+            shape: Shape { sets: Vec::new(), pair: None, tiles },
+            wait: None,
+            closed: true,
+        }),
+        false => None,
+    }
+}
+
+// The fan of one reading of a hand won on `tile`, flowers aside. `sole_wait`
+// is whether the winning tile was the only one the hand could have won on.
+pub(super) fn find_fan(reading: &Reading, tile: MahjongTile, context: &WinContext, sole_wait: bool) -> Vec<Fan> {
+    let shape = &reading.shape;
+    let tiles = &shape.tiles;
+    let chows: Vec<TileIndex> = shape.sets.iter().filter(|s| !s.is_triplet()).map(|s| s.tile).collect();
+    let pungs: Vec<&Set> = shape.sets.iter().filter(|s| s.is_triplet()).collect();
+    let suits = shape.suits();
+    let honors = tiles.iter().any(|t| t.is_honour());
+    let mut found = Vec::new();
+
+    match reading.form {
+        Form::SevenPairs => {
+            found.push(Fan::SevenPairs);
+            let mut values: Vec<i8> = tiles.iter().filter_map(|t| t.value()).collect();
+            values.dedup();
+            if suits.len() == 1 && !honors && values.len() == 7 && values[6] - values[0] == 6 {
+                found.push(Fan::SevenShiftedPairs);
+            }
+        }
+        Form::ThirteenOrphans => found.push(Fan::ThirteenOrphans),
+        Form::KnittedStraight => found.push(Fan::KnittedStraight),
+        Form::HonorsAndKnitted => {
+            match tiles.iter().filter(|t| t.is_honour()).count() {
+                7 => found.push(Fan::GreaterHonorsAndKnittedTiles),
+                _ => found.push(Fan::LesserHonorsAndKnittedTiles),
+            }
+            if KNITS.iter().any(|suits| knitted(*suits).iter().all(|t| tiles.contains(t))) {
+                found.push(Fan::KnittedStraight);
+            }
+        }
+        Form::Standard => (),
+    }
+
+    // Winds and dragons
+    let wind_pungs = pungs.iter().filter(|p| is_wind(p.tile)).count();
+    let wind_pair = shape.pair.is_some_and(is_wind);
+    match wind_pungs {
+        4 => found.push(Fan::BigFourWinds),
+        3 if wind_pair => found.push(Fan::LittleFourWinds),
+        3 => found.push(Fan::BigThreeWinds),
+        _ => (),
+    }
+    let dragon_pungs = pungs.iter().filter(|p| is_dragon(p.tile)).count();
+    match dragon_pungs {
+        3 => found.push(Fan::BigThreeDragons),
+        2 if shape.pair.is_some_and(is_dragon) => found.push(Fan::LittleThreeDragons),
+        2 => found.push(Fan::TwoDragonPungs),
+        _ => (),
+    }
+    for pung in &pungs {
+        match pung.tile.tile() {
+            MahjongTile::Dragon(_) => found.push(Fan::DragonPung),
+            MahjongTile::Wind(wind) => {
+                if wind == context.round_wind {
+                    found.push(Fan::PrevalentWind);
+                }
+                if wind == context.seat_wind {
+                    found.push(Fan::SeatWind);
+                }
+                // Three wind pungs already count them all
+                if wind != context.round_wind && wind != context.seat_wind && wind_pungs < 3 {
+                    found.push(Fan::PungOfTerminalsOrHonors);
+                }
+            }
+            _ if pung.tile.is_terminal() => found.push(Fan::PungOfTerminalsOrHonors),
+            _ => (),
+        }
+    }
+
+    // The tiles the hand is made of
+    if tiles.iter().all(|t| is_green(*t)) {
+        found.push(Fan::AllGreen);
+    }
+    if tiles.iter().all(|t| t.is_honour()) {
+        found.push(Fan::AllHonors);
+    } else if tiles.iter().all(|t| t.is_terminal()) {
+        found.push(Fan::AllTerminals);
+    } else if tiles.iter().all(|t| t.is_terminal_or_honour()) {
+        found.push(Fan::AllTerminalsAndHonors);
+    }
+    if reading.closed && TileIndex::try_from(tile).is_ok_and(|tile| nine_gates(reading, tiles, tile)) {
+        found.push(Fan::NineGates);
+    }
+    match (suits.len(), honors) {
+        (1, false) => found.push(Fan::FullFlush),
+        (1, true) => found.push(Fan::HalfFlush),
+        (2, _) => found.push(Fan::OneVoidedSuit),
+        _ => (),
+    }
+    if !honors {
+        found.push(Fan::NoHonors);
+    }
+    let values: Vec<i8> = tiles.iter().filter_map(|t| t.value()).collect();
+    let within = |low: i8, high: i8| !honors && values.iter().all(|v| (low..=high).contains(v));
+    if within(7, 9) {
+        found.push(Fan::UpperTiles);
+    } else if within(4, 6) {
+        found.push(Fan::MiddleTiles);
+    } else if within(1, 3) {
+        found.push(Fan::LowerTiles);
+    } else if within(6, 9) {
+        found.push(Fan::UpperFour);
+    } else if within(1, 4) {
+        found.push(Fan::LowerFour);
+    }
+    if tiles.iter().all(|t| is_reversible(*t)) {
+        found.push(Fan::ReversibleTiles);
+    }
+    if tiles.iter().all(|t| !t.is_terminal_or_honour()) {
+        found.push(Fan::AllSimples);
+    }
+    let winds = tiles.iter().any(|t| is_wind(*t));
+    let dragons = tiles.iter().any(|t| is_dragon(*t));
+    if suits.len() == 3 && winds && dragons {
+        found.push(Fan::AllTypes);
+    }
+
+    if reading.form == Form::Standard {
+        found.extend(standard_fan(reading, context, &chows, &pungs));
+    }
+    for (index, count) in HandCounts::from_tiles(&tiles.iter().map(|t| t.tile()).collect::<Vec<_>>()).iter() {
+        let in_kong = pungs.iter().any(|p| p.kind == SetKind::Kong && p.tile == index);
+        if count == 4 && !in_kong {
+            found.push(Fan::TileHog);
+        }
+    }
+
+    // How the hand was won
+    if reading.closed {
+        found.push(if context.tsumo { Fan::FullyConcealedHand } else { Fan::ConcealedHand });
+    }
+    if context.tsumo {
+        found.push(Fan::SelfDrawn);
+    }
+    if context.last_tile {
+        match (context.tsumo, context.rinshan) {
+            (true, false) => found.push(Fan::LastTileDraw),
+            (false, _) => found.push(Fan::LastTileClaim),
+            _ => (),
+        }
+    }
+    if context.rinshan && context.tsumo {
+        found.push(Fan::OutWithReplacementTile);
+    }
+    if context.chankan {
+        found.push(Fan::RobbingTheKong);
+    }
+    if context.last_of_kind {
+        found.push(Fan::LastTile);
+    }
+    if sole_wait {
+        match reading.wait {
+            Some(WaitKind::Penchan) => found.push(Fan::EdgeWait),
+            Some(WaitKind::Kanchan) => found.push(Fan::ClosedWait),
+            Some(WaitKind::Tanki) => found.push(Fan::SingleWait),
+            _ => (),
+        }
+    }
+
+    let implied: Vec<Fan> = found.iter().flat_map(|f| f.implies().iter().copied()).collect();
+    found.retain(|f| !implied.contains(f));
+    if found.is_empty() {
+        found.push(Fan::ChickenHand);
+    }
+    found
+}
+
+// The fan that come from the four sets of a standard hand
+fn standard_fan(reading: &Reading, context: &WinContext, chows: &[TileIndex], pungs: &[&Set]) -> Vec<Fan> {
+    let shape = &reading.shape;
+    let pair = shape.pair.unwrap();
+    let mut found = Vec::new();
+
+    // Pungs and kongs
+    if pungs.len() == 4 {
+        found.push(Fan::AllPungs);
+        if shape.tiles.iter().all(|t| t.value().is_some_and(|v| v % 2 == 0)) {
+            found.push(Fan::AllEvenPungs);
+        }
+    }
+    match pungs.iter().filter(|p| p.concealed).count() {
+        4 => found.push(Fan::FourConcealedPungs),
+        3 => found.push(Fan::ThreeConcealedPungs),
+        2 => found.push(Fan::TwoConcealedPungs),
+        _ => (),
+    }
+    let kongs = pungs.iter().filter(|p| p.kind == SetKind::Kong).count();
+    let concealed_kongs = pungs.iter().filter(|p| p.kind == SetKind::Kong && p.concealed).count();
+    match (kongs, concealed_kongs) {
+        (4, _) => found.push(Fan::FourKongs),
+        (3, _) => found.push(Fan::ThreeKongs),
+        (2, 2) => found.push(Fan::TwoConcealedKongs),
+        (2, 1) => found.extend([Fan::ConcealedKong, Fan::MeldedKong]),
+        (2, _) => found.push(Fan::TwoMeldedKongs),
+        (1, 1) => found.push(Fan::ConcealedKong),
+        (1, _) => found.push(Fan::MeldedKong),
+        _ => (),
+    }
+    let suited: Vec<TileIndex> = pungs.iter().map(|p| p.tile).filter(|t| t.suit().is_some()).collect();
+    found.extend(pung_fan(&suited));
+
+    // Chows, with one fan from three or four of them and then at most one
+    // fan for each pairing that brings in a chow not yet counted
+    let (big, grouped) = chow_fan(chows, pair);
+    let implied: Vec<Fan> = big.iter().flat_map(|f| f.implies().iter().copied()).collect();
+    found.extend(big);
+    let mut joined: Vec<usize> = (0..chows.len()).collect();
+    if let Some(first) = grouped.first() {
+        grouped.iter().for_each(|i| joined[*i] = *first);
+    }
+    let mut pairs: Vec<(Fan, usize, usize)> = Vec::new();
+    for i in 0..chows.len() {
+        for j in i + 1..chows.len() {
+            if let Some(fan) = chow_pair(chows[i], chows[j]).filter(|f| !implied.contains(f)) {
+                pairs.push((fan, i, j));
+            }
+        }
+    }
+    // The more valuable pairings first
+    pairs.sort();
+    for (fan, i, j) in pairs {
+        let (a, b) = (root(&joined, i), root(&joined, j));
+        if a != b {
+            joined[a] = b;
+            found.push(fan);
+        }
+    }
+
+    let all_sets = shape.sets.iter().all(|s| s.has_terminal_or_honour());
+    if all_sets && pair.is_terminal_or_honour() {
+        found.push(Fan::OutsideHand);
+    }
+    let fives = shape.sets.iter().all(|s| match s.kind {
+        SetKind::Chow => s.tile.value().is_some_and(|v| (3..=5).contains(&v)),
+        _ => s.tile.value() == Some(5),
+    });
+    if fives && pair.value() == Some(5) {
+        found.push(Fan::AllFives);
+    }
+    if chows.len() == 4 && !pair.is_honour() {
+        found.push(Fan::AllChows);
+    }
+    let melded = shape.sets.iter().all(|s| !s.concealed);
+    if melded && reading.wait == Some(WaitKind::Tanki) && !reading.closed && !context.tsumo {
+        found.push(Fan::MeldedHand);
+    }
+    found
+}
+
+fn root(joined: &[usize], mut i: usize) -> usize {
+    while joined[i] != i {
+        i = joined[i];
+    }
+    i
+}
+
+// Fan from suited pungs lined up by value or across suits
+fn pung_fan(pungs: &[TileIndex]) -> Vec<Fan> {
+    let mut found = Vec::new();
+    let same_suit_run = |count: usize| subsets(pungs, count).iter().any(|set| {
+        let suit = set[0].suit();
+        let mut values: Vec<i8> = set.iter().filter_map(|t| t.value()).collect();
+        values.sort();
+        set.iter().all(|t| t.suit() == suit) && values.windows(2).all(|w| w[1] - w[0] == 1)
+    });
+    let mixed = |count: usize, step: i8| subsets(pungs, count).iter().any(|set| {
+        let mut set = set.clone();
+        set.sort_by_key(|t| t.value());
+        distinct_suits(&set) && set.windows(2).all(|w| w[1].value().unwrap() - w[0].value().unwrap() == step)
+    });
+    if same_suit_run(4) {
+        found.push(Fan::FourPureShiftedPungs);
+    } else if same_suit_run(3) {
+        found.push(Fan::PureShiftedPungs);
+    }
+    if mixed(3, 0) {
+        found.push(Fan::TriplePung);
+    } else if mixed(3, 1) {
+        found.push(Fan::MixedShiftedPungs);
+    }
+    for pair in subsets(pungs, 2) {
+        if pair[0].value() == pair[1].value() && pair[0].suit() != pair[1].suit() {
+            found.push(Fan::DoublePung);
+        }
+    }
+    found
+}
+
+// The best fan made by three or four of the chows, and which chows it took
+fn chow_fan(chows: &[TileIndex], pair: TileIndex) -> (Vec<Fan>, Vec<usize>) {
+    let all: Vec<usize> = (0..chows.len()).collect();
+    if chows.len() == 4 {
+        let mut sorted = chows.to_vec();
+        sorted.sort();
+        let suit = sorted[0].suit();
+        let same_suit = sorted.iter().all(|c| c.suit() == suit);
+        let steps: Vec<i8> = sorted.windows(2).map(|w| w[1].value().unwrap() - w[0].value().unwrap()).collect();
+        if same_suit && steps == [0, 0, 0] {
+            return (vec![Fan::QuadrupleChow], all);
+        }
+        if same_suit && pair.suit() == suit && pair.value() == Some(5) && steps == [0, 6, 0] && sorted[0].value() == Some(1) {
+            return (vec![Fan::PureTerminalChows], all);
+        }
+        if same_suit && (steps == [1, 1, 1] || steps == [2, 2, 2]) {
+            return (vec![Fan::FourPureShiftedChows], all);
+        }
+        let terminal_suits: Vec<Option<Suit>> = sorted.iter()
+            .filter(|c| c.value() == Some(1))
+            .filter(|c| sorted.iter().any(|o| o.suit() == c.suit() && o.value() == Some(7)))
+            .map(|c| c.suit())
+            .collect();
+        if terminal_suits.len() == 2 && terminal_suits[0] != terminal_suits[1]
+            && pair.value() == Some(5) && !terminal_suits.contains(&pair.suit()) {
+            return (vec![Fan::ThreeSuitedTerminalChows], all);
+        }
+    }
+    let mut best: (Vec<Fan>, Vec<usize>) = (Vec::new(), Vec::new());
+    for picked in subsets(&all, 3) {
+        let mut set: Vec<TileIndex> = picked.iter().map(|i| chows[*i]).collect();
+        set.sort_by_key(|c| c.value());
+        let Some(fan) = three_chows(&set) else { continue };
+        if best.0.first().is_none_or(|b| b.points() < fan.points()) {
+            best = (vec![fan], picked);
+        }
+    }
+    best
+}
+
+// `chows` sorted by value
+fn three_chows(chows: &[TileIndex]) -> Option<Fan> {
+    let values: Vec<i8> = chows.iter().map(|c| c.value().unwrap()).collect();
+    let suit = chows[0].suit();
+    let same_suit = chows.iter().all(|c| c.suit() == suit);
+    let steps = [values[1] - values[0], values[2] - values[1]];
+    match (same_suit, distinct_suits(chows), steps) {
+        (true, _, [0, 0]) => Some(Fan::PureTripleChow),
+        (true, _, [3, 3]) => Some(Fan::PureStraight),
+        (true, _, [1, 1] | [2, 2]) => Some(Fan::PureShiftedChows),
+        (_, true, [3, 3]) => Some(Fan::MixedStraight),
+        (_, true, [0, 0]) => Some(Fan::MixedTripleChow),
+        (_, true, [1, 1]) => Some(Fan::MixedShiftedChows),
+        _ => None,
+    }
+}
+
+fn chow_pair(a: TileIndex, b: TileIndex) -> Option<Fan> {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let (low_value, high_value) = (low.value()?, high.value()?);
+    match (low.suit() == high.suit(), high_value - low_value) {
+        (true, 0) => Some(Fan::PureDoubleChow),
+        (true, 3) => Some(Fan::ShortStraight),
+        (true, 6) => Some(Fan::TwoTerminalChows),
+        (false, 0) => Some(Fan::MixedDoubleChow),
+        _ => None,
+    }
+}
+
+fn distinct_suits(tiles: &[TileIndex]) -> bool {
+    tiles.iter().enumerate().all(|(i, t)| tiles[..i].iter().all(|o| o.suit() != t.suit()))
+}
+
+fn subsets<T: Copy>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    let mut found = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        for mut rest in subsets(&items[i + 1..], size - 1) {
+            rest.insert(0, *item);
+            found.push(rest);
+        }
+    }
+    found
+}
+
+// 1112345678999 in one suit held before the win, the winning tile any of it
+fn nine_gates(reading: &Reading, tiles: &[TileIndex], tile: TileIndex) -> bool {
+    if reading.shape.suits().len() != 1 || tiles.iter().any(|t| t.is_honour()) || tiles.len() != 14 {
+        return false;
+    }
+    let mut counts = [0u8; 9];
+    tiles.iter().for_each(|t| counts[t.value().unwrap() as usize - 1] += 1);
+    counts[tile.value().unwrap() as usize - 1] -= 1;
+    counts == [3, 1, 1, 1, 1, 1, 1, 1, 3]
+}
+
+fn is_dragon(tile: TileIndex) -> bool {
+    matches!(tile.tile(), MahjongTile::Dragon(_))
+}
+
+fn is_wind(tile: TileIndex) -> bool {
+    matches!(tile.tile(), MahjongTile::Wind(_))
+}
+
+// 2, 3, 4, 6 and 8 sou and the green dragon
+fn is_green(tile: TileIndex) -> bool {
+    match tile.tile() {
+        MahjongTile::Dragon(Dragon::Green) => true,
+        MahjongTile::Suit(suited) => suited.suit == Suit::Sou && matches!(suited.value, 2 | 3 | 4 | 6 | 8),
+        _ => false,
+    }
+}
+
+// Tiles that look the same upside down: 1234589 pin, 245689 sou and white
+fn is_reversible(tile: TileIndex) -> bool {
+    match tile.tile() {
+        MahjongTile::Dragon(Dragon::White) => true,
+        MahjongTile::Suit(suited) => match suited.suit {
+            Suit::Pin => matches!(suited.value, 1 | 2 | 3 | 4 | 5 | 8 | 9),
+            Suit::Sou => matches!(suited.value, 2 | 4 | 5 | 6 | 8 | 9),
+            Suit::Man => false,
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,2 @@
+pub mod fan;
+pub mod score;
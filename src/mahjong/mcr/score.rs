@@ -0,0 +1,198 @@
+use std::fmt;
+
+use crate::mahjong::strategy::block_strategy::Meld;
+use crate::mahjong::tile::{hand_counts::HandCounts, mahjong_tile::MahjongTile, tile_index::TileIndex};
+use crate::mahjong::yaku::context::WinContext;
+
+use super::fan::{find_fan, mcr_readings, total_fan, Fan};
+
+// Every other player pays this on top of the fan owed by whoever pays for the
+// win
+pub const BASE: u32 = 8;
+
+// The value of a winning hand under the Chinese Official rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McrScore {
+    pub fan: Vec<Fan>,
+    // One fan each, which don't count towards the minimum
+    pub flowers: u32,
+    // Flowers included
+    pub total: u32,
+    pub tsumo: bool,
+}
+
+impl McrScore {
+    // What the discarder pays for a win off their tile, or every player for
+    // a self-drawn one
+    pub fn from_payer(&self) -> u32 {
+        BASE + self.total
+    }
+
+    // What each of the other two pays for a win off a discard
+    pub fn from_others(&self) -> u32 {
+        BASE
+    }
+
+    // What the winner takes in all
+    pub fn gain(&self) -> u32 {
+        match self.tsumo {
+            true => 3 * self.from_payer(),
+            false => self.from_payer() + 2 * self.from_others(),
+        }
+    }
+}
+
+impl fmt::Display for McrScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fan: Vec<String> = self.fan.iter().map(|fan| format!("{:?} {}", fan, fan.points())).collect();
+        if self.flowers > 0 {
+            fan.push(format!("{:?} {}", Fan::FlowerTiles, self.flowers));
+        }
+        write!(f, "{} ({} fan)", fan.join(", "), self.total)
+    }
+}
+
+// The best reading of a hand won on `tile`, with `concealed` already holding
+// it. None if the hand isn't complete or falls short of `minimum` fan before
+// its flowers.
+pub fn best_score(concealed: &HandCounts, called: &[Meld], tile: MahjongTile, context: &WinContext, flowers: u32, minimum: u32) -> Option<McrScore> {
+    let readings = mcr_readings(concealed, called, tile, context);
+    if readings.is_empty() {
+        return None;
+    }
+    let sole_wait = winning_kinds(concealed, called, tile, context) == 1;
+    readings.iter()
+        .map(|reading| find_fan(reading, tile, context, sole_wait))
+        .filter(|fan| total_fan(fan) >= minimum)
+        .max_by_key(|fan| total_fan(fan))
+        .map(|fan| McrScore { total: total_fan(&fan) + flowers, fan, flowers, tsumo: context.tsumo })
+}
+
+// How many tile kinds the hand was waiting on before `tile`, knitted hands
+// included
+fn winning_kinds(concealed: &HandCounts, called: &[Meld], tile: MahjongTile, context: &WinContext) -> usize {
+    let mut before = *concealed;
+    before.remove(tile);
+    TileIndex::all()
+        .filter(|index| before.count(*index) < 4)
+        .filter(|index| {
+            let mut hand = before;
+            hand.add(*index);
+            !mcr_readings(&hand, called, index.tile(), context).is_empty()
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mahjong::tile::{enums::Wind, notation::parse_hand};
+
+    use super::*;
+
+    // `hand` holds all fourteen tiles, the winning one included
+    fn fan(hand: &str, tile: &str, context: WinContext) -> Vec<Fan> {
+        scored(hand, tile, context).unwrap().fan
+    }
+
+    fn scored(hand: &str, tile: &str, context: WinContext) -> Option<McrScore> {
+        let hand = parse_hand(hand).unwrap();
+        best_score(&HandCounts::from_tiles(&hand.tiles), &hand.melds, tile.parse().unwrap(), &context, 0, 0)
+    }
+
+    fn ron() -> WinContext {
+        WinContext::new(Wind::South, Wind::East)
+    }
+
+    fn tsumo() -> WinContext {
+        WinContext { tsumo: true, ..ron() }
+    }
+
+    #[test]
+    fn chicken_hand() {
+        // Open, unrelated chows in all three suits, an honour pair and a
+        // two-sided wait off someone else's discard
+        assert_eq!(fan("234m567p11z[678s][345p]", "4m", ron()), vec![Fan::ChickenHand]);
+        assert_eq!(fan("234m567p88s[678s][555z]", "8s", ron()), vec![Fan::DragonPung, Fan::SingleWait]);
+    }
+
+    #[test]
+    fn implied_fan_are_left_out() {
+        // Fully concealed hand takes in self-drawn and concealed hand, and all
+        // chows no honours
+        let hand = fan("234m567p678s99s234p", "4m", tsumo());
+        assert_eq!(hand, vec![Fan::MixedDoubleChow, Fan::ShortStraight, Fan::AllChows, Fan::FullyConcealedHand]);
+        // Full flush takes in no honours, pure straight the short straights
+        let flush = fan("123456789m11m[789m]", "9m", ron());
+        assert!(flush.contains(&Fan::FullFlush) && flush.contains(&Fan::PureStraight));
+        assert!(!flush.contains(&Fan::NoHonors) && !flush.contains(&Fan::ShortStraight));
+        let all_green = fan("234s234s666s888s66z", "6z", ron());
+        assert_eq!(all_green[0], Fan::AllGreen);
+        assert!(!all_green.contains(&Fan::HalfFlush));
+    }
+
+    #[test]
+    fn chows_are_each_paired_once() {
+        // 123m 123m 123p 123p: a pure double chow twice and one mixed double
+        // chow, since the fourth pairing adds no chow not yet counted
+        let hand = fan("123m123p99s[123m][123p]", "9s", ron());
+        assert_eq!(hand.iter().filter(|f| **f == Fan::PureDoubleChow).count(), 2);
+        assert_eq!(hand.iter().filter(|f| **f == Fan::MixedDoubleChow).count(), 1);
+    }
+
+    #[test]
+    fn winds_and_dragons() {
+        let context = WinContext::new(Wind::West, Wind::East);
+        let hand = fan("111z333z22m[456p][555z]", "2m", context);
+        assert!(hand.contains(&Fan::PrevalentWind) && hand.contains(&Fan::SeatWind) && hand.contains(&Fan::DragonPung));
+        let big = fan("111z222z333z444z22m", "2m", ron());
+        assert!(big.contains(&Fan::BigFourWinds) && !big.contains(&Fan::PrevalentWind));
+    }
+
+    #[test]
+    fn waits_count_only_when_sole() {
+        // 13m waiting on 2m alone
+        assert!(fan("13m2m456p789p567s99s", "2m", ron()).contains(&Fan::ClosedWait));
+        // 2345m could take 2m or 5m for the pair
+        assert!(!fan("2345m5m456p789p567s", "5m", ron()).contains(&Fan::SingleWait));
+    }
+
+    #[test]
+    fn knitted_hands() {
+        // 147m 258p 369s with a chow and a pair
+        let straight = fan("147m258p369s123m55z", "5z", ron());
+        assert!(straight.contains(&Fan::KnittedStraight));
+        assert_eq!(fan("147m25p36s1234567z", "7z", ron()), vec![Fan::GreaterHonorsAndKnittedTiles]);
+        let lesser = fan("147m258p369s12345z", "5z", ron());
+        assert_eq!(lesser, vec![Fan::LesserHonorsAndKnittedTiles, Fan::KnittedStraight]);
+        assert_eq!(fan("19m19p19s12345677z", "7z", ron()), vec![Fan::ThirteenOrphans]);
+    }
+
+    #[test]
+    fn nine_gates_needs_the_exact_shape_before_the_win() {
+        assert!(fan("11123455678999m", "5m", ron()).contains(&Fan::NineGates));
+        // 1112234567899 only covers 1112345678999 with the winning 9 added
+        assert!(!fan("11122345678999m", "9m", ron()).contains(&Fan::NineGates));
+    }
+
+    #[test]
+    fn melded_hand_is_won_off_a_discard() {
+        let melded = "22m[123p][456p][789s][345s]";
+        assert!(fan(melded, "2m", ron()).contains(&Fan::MeldedHand));
+        assert!(!fan(melded, "2m", tsumo()).contains(&Fan::MeldedHand));
+    }
+
+    #[test]
+    fn minimum_and_payment() {
+        let hand = parse_hand("234m567p11z[678s][345p]").unwrap();
+        let counts = HandCounts::from_tiles(&hand.tiles);
+        assert_eq!(best_score(&counts, &hand.melds, "4m".parse().unwrap(), &ron(), 0, 8).map(|s| s.total), Some(8));
+        // Flowers don't help a hand reach the minimum
+        let hand = parse_hand("234m567p88s[678s][555z]").unwrap();
+        let counts = HandCounts::from_tiles(&hand.tiles);
+        assert_eq!(best_score(&counts, &hand.melds, "8s".parse().unwrap(), &ron(), 6, 8), None);
+        let score = best_score(&counts, &hand.melds, "8s".parse().unwrap(), &ron(), 6, 1).unwrap();
+        assert_eq!((score.total, score.from_payer(), score.from_others(), score.gain()), (9, 17, 8, 33));
+        let tsumo = scored("234m567p678s99s234p", "4m", tsumo()).unwrap();
+        assert_eq!((tsumo.total, tsumo.gain()), (8, 48));
+    }
+}
@@ -1,6 +1,12 @@
+pub mod analysis;
 pub mod game;
-mod player;
-mod tile;
-mod wall;
-mod strategy;
-mod player_state;
\ No newline at end of file
+pub mod game_match;
+pub mod player;
+pub mod tile;
+pub mod wall;
+pub mod strategy;
+pub mod player_state;
+pub mod yaku;
+pub mod table;
+pub mod ruleset;
+pub mod mcr;
@@ -1,2 +1,3 @@
+#[allow(clippy::module_inception)]
 pub mod player;
 pub mod strategic_player;
\ No newline at end of file
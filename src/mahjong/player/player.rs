@@ -1,15 +1,68 @@
-use crate::mahjong::{tile::mahjong_tile::MahjongTile};
+use crate::mahjong::{strategy::block_strategy::Meld, tile::{hand_counts::HandCounts, mahjong_tile::MahjongTile}};
 
 // Players handle an AI or Human player
 // Players have no access to their own hand state, they are simply given references
 // this prevents players from cheating
 pub trait Player {
-  fn turn(&self, game_state: &Vec<MahjongTile>) -> TurnState;
+  fn turn(&self, game_state: &[MahjongTile], table: &TableView) -> TurnState;
+  // Another player has discarded `tile`. `options` holds every claim the
+  // player may make on it; anything else is a chombo.
+  fn offer_discard(&self, game_state: &[MahjongTile], tile: MahjongTile, options: &[DiscardResponse], table: &TableView) -> DiscardResponse;
 }
 
+// Everything about the table a player is allowed to know
+pub struct TableView {
+  // Every discard, the tiles shown in called melds and the revealed dora
+  // indicators
+  pub visible: HandCounts,
+  // The player's own called melds
+  pub melds: Vec<Meld>,
+  // Whether a kan may be declared this turn. Not straight after a chi or
+  // pon, and not once four kans are down or the wall has run out.
+  pub can_kan: bool,
+  // The tile just drawn completes the hand with at least one yaku
+  pub can_tsumo: bool,
+  // Kyuushu kyuuhai may be declared to abort the hand
+  pub can_kyuushu: bool,
+  // A North may be set aside as nukidora, in sanma
+  pub can_nukidora: bool,
+  // Riichi has been declared, so every draw that doesn't win is thrown
+  pub riichi: bool,
+  // The hand is closed, the player has the points for the stick and the
+  // wall has a draw left for them. Being tenpai is up to the player.
+  pub can_riichi: bool,
+  // Ron is barred, see `PlayerState::is_furiten`
+  pub(crate) furiten: bool,
+}
+
+impl TableView {
+  pub fn is_furiten(&self) -> bool {
+    self.furiten
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TurnState {
-  Draw,
   Discard(MahjongTile),
   Riichi(MahjongTile),
   Tsumo,
-}
\ No newline at end of file
+  // Nine kinds of terminal and honour on the first draw, ending the hand
+  KyuushuKyuuhai,
+  // Ankan, four of the tile from hand
+  ClosedKan(MahjongTile),
+  // Shouminkan, the tile added to a called pung
+  AddedKan(MahjongTile),
+  // Sets a North aside for a dora and draws a replacement
+  Nukidora,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscardResponse {
+  Pass,
+  Ron,
+  Pon,
+  // Daiminkan, an open kan on a discard
+  Kan,
+  // The two tiles from hand that make the run with the discard
+  Chi(MahjongTile, MahjongTile),
+}
@@ -1,4 +1,4 @@
-use crate::mahjong::{player::player::Player, player::player::TurnState, strategy::{strategy::{Strategy}, block_strategy::BlockStrategy}, tile::mahjong_tile::MahjongTile};
+use crate::mahjong::{analysis::shanten::shanten, player::player::{DiscardResponse, Player, TableView, TurnState}, strategy::{strategy::{Strategy}, block_strategy::{BlockStrategy, Meld}}, tile::{hand_counts::HandCounts, mahjong_tile::MahjongTile}};
 
 
 pub struct StrategicPlayer {
@@ -11,6 +11,25 @@ impl StrategicPlayer {
             strategy: Box::new(BlockStrategy{})
         }
     }
+
+    // Adding to a called pung costs nothing, while a closed kan is only
+    // worth it when the four tiles aren't doing anything else
+    fn kan(&self, counts: &HandCounts, table: &TableView) -> Option<TurnState> {
+        let pung = table.melds.iter()
+            .find(|m| matches!(m, Meld::Pung(_)) && counts.contains(m.tiles()[0]));
+        if let Some(pung) = pung {
+            return Some(TurnState::AddedKan(pung.tiles()[0]));
+        }
+        let current = shanten(counts, table.melds.len());
+        counts.iter()
+            .filter(|(_, count)| *count == 4)
+            .find(|(index, _)| {
+                let mut rest = *counts;
+                (0..4).for_each(|_| { rest.remove(*index); });
+                shanten(&rest, table.melds.len() + 1) <= current
+            })
+            .map(|(index, _)| TurnState::ClosedKan(index.tile()))
+    }
 }
 
 impl Default for StrategicPlayer {
@@ -20,15 +39,57 @@ impl Default for StrategicPlayer {
 }
 
 impl Player for StrategicPlayer {
-    fn turn(&self, hand: &Vec<MahjongTile>) -> TurnState {
-        if hand.len() < 14 {
-            return TurnState::Draw
-        }
-        if self.strategy.winning(hand) {
+    fn turn(&self, hand: &[MahjongTile], table: &TableView) -> TurnState {
+        let counts = HandCounts::from_tiles(hand);
+        if table.can_tsumo {
             return TurnState::Tsumo
         }
-        let mut x = self.strategy.discard(hand);
-        x.sort_by(|(_, a), (_, b)| a.cmp(b));
-        TurnState::Discard(x.first().unwrap().0)
+        // Nine kinds leave the hand a long way from anything but kokushi
+        if table.can_kyuushu && shanten(&counts, 0) >= 3 {
+            return TurnState::KyuushuKyuuhai;
+        }
+        // A North does nothing in sanma but wait for a pair
+        if table.can_nukidora {
+            return TurnState::Nukidora;
+        }
+        if table.can_kan {
+            if let Some(kan) = self.kan(&counts, table) {
+                return kan;
+            }
+        }
+        if table.riichi {
+            return TurnState::Discard(hand[hand.len() - 1]);
+        }
+        let mut visible = table.visible;
+        visible.extend(hand.iter().copied());
+        let mut x = self.strategy.discard(&counts, &table.melds, &visible);
+        x.sort_by_key(|(_, weight)| *weight);
+        let discard = x.first().unwrap().0;
+        if table.can_riichi && self.strategy.riichi(&counts, &table.melds, discard) {
+            return TurnState::Riichi(discard);
+        }
+        TurnState::Discard(discard)
     }
-}
\ No newline at end of file
+
+    fn offer_discard(&self, hand: &[MahjongTile], tile: MahjongTile, options: &[DiscardResponse], table: &TableView) -> DiscardResponse {
+        let counts = HandCounts::from_tiles(hand);
+        let mut won = counts;
+        won.add(tile);
+        if options.contains(&DiscardResponse::Ron) && self.strategy.winning(&won, &table.melds) {
+            return DiscardResponse::Ron;
+        }
+        // Dragon pungs always carry a yaku, so they're worth opening the hand
+        // for as long as they bring it closer to winning
+        if let MahjongTile::Dragon(_) = tile {
+            if options.contains(&DiscardResponse::Pon) {
+                let mut called = counts;
+                called.remove(tile);
+                called.remove(tile);
+                if shanten(&called, table.melds.len() + 1) < shanten(&counts, table.melds.len()) {
+                    return DiscardResponse::Pon;
+                }
+            }
+        }
+        DiscardResponse::Pass
+    }
+}
@@ -1 +1,2 @@
-pub mod player_state;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod player_state;
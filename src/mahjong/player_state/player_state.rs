@@ -1,33 +1,304 @@
-use crate::mahjong::{tile::{enums::Wind, mahjong_tile::MahjongTile}, player::{strategic_player::StrategicPlayer, player::{TurnState, Player}}};
+use crate::mahjong::{analysis::{agari::is_agari, machi::{is_tenpai, winning_tiles}}, strategy::block_strategy::{Called, Chow, Kong, Meld, Pung}, tile::{enums::Wind, hand_counts::HandCounts, mahjong_tile::MahjongTile, tile_index::TileIndex}, ruleset::ruleset::Scoring, player::{strategic_player::StrategicPlayer, player::{DiscardResponse, TableView, TurnState, Player}}};
+
+pub const STARTING_POINTS: i32 = 25000;
+pub const RIICHI_STICK: i32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Riichi {
+  // Declared with the first discard, before any call
+  pub double: bool,
+  // Still within the go-around after declaring, with no calls in between
+  pub ippatsu: bool,
+}
 
 pub struct PlayerState {
   wind: Wind,
   discards: Vec<MahjongTile>,
-  player: StrategicPlayer,
-  hand: Vec<MahjongTile>
+  player: Box<dyn Player>,
+  hand: Vec<MahjongTile>,
+  hand_counts: HandCounts,
+  melds: Vec<Meld>,
+  // Norths set aside in sanma
+  nukidora: Vec<MahjongTile>,
+  // Flowers set aside under the Chinese Official rules
+  flowers: Vec<MahjongTile>,
+  points: i32,
+  riichi: Option<Riichi>,
+  // Let a winning tile go by since the last discard
+  passed_win: bool,
+  // Let a winning tile go by after declaring riichi, which lasts the hand
+  riichi_furiten: bool,
+  // Another player has called one of our discards
+  discard_called: bool,
 }
 
 impl PlayerState {
     pub fn new(wind: Wind) -> Self {
-        PlayerState { player: StrategicPlayer::new(), hand: Vec::new(), wind, discards: Vec::new() }
+        Self::with_player(wind, Box::new(StrategicPlayer::new()))
+    }
+
+    pub fn with_player(wind: Wind, player: Box<dyn Player>) -> Self {
+        PlayerState { player, hand: Vec::new(), hand_counts: HandCounts::new(), melds: Vec::new(), nukidora: Vec::new(), flowers: Vec::new(), points: STARTING_POINTS, riichi: None, passed_win: false, riichi_furiten: false, discard_called: false, wind, discards: Vec::new() }
     }
 
     pub fn wind(&self) -> Wind {
       self.wind
     }
 
-    pub fn turn(&mut self, tile: MahjongTile) -> TurnState {
-      self.hand.push(tile.clone());
-      self.player.turn(&self.hand)
+    pub fn discards(&self) -> &[MahjongTile] {
+      &self.discards
+    }
+
+    pub fn hand_counts(&self) -> &HandCounts {
+      &self.hand_counts
+    }
+
+    pub fn hand(&self) -> &[MahjongTile] {
+      &self.hand
+    }
+
+    pub fn melds(&self) -> &[Meld] {
+      &self.melds
+    }
+
+    pub fn nukidora(&self) -> &[MahjongTile] {
+      &self.nukidora
+    }
+
+    pub fn flowers(&self) -> &[MahjongTile] {
+      &self.flowers
+    }
+
+    pub fn points(&self) -> i32 {
+      self.points
+    }
+
+    pub fn riichi(&self) -> Option<Riichi> {
+      self.riichi
+    }
+
+    // Barred from ron: a wait is among the player's own discards, or a
+    // winning tile has been let go since their last discard, or at all since
+    // declaring riichi. Only meaningful between turns, with 13 tiles held.
+    pub fn is_furiten(&self) -> bool {
+      self.passed_win || self.riichi_furiten
+        || winning_tiles(&self.hand_counts, &self.melds).iter().any(|t| self.discards.contains(t))
+    }
+
+    // A tile the hand could have won on went by unclaimed
+    pub fn pass_winning_tile(&mut self) {
+      self.passed_win = true;
+      if self.riichi.is_some() {
+        self.riichi_furiten = true;
+      }
+    }
+
+    // Only meaningful between turns, with 13 tiles held
+    pub fn is_tenpai(&self) -> bool {
+      is_tenpai(&self.hand_counts, &self.melds)
+    }
+
+    // Nagashi mangan: every discard a terminal or honour, and none of them
+    // called
+    pub fn is_nagashi(&self) -> bool {
+      !self.discard_called
+        && !self.discards.is_empty()
+        && self.discards.iter().all(|t| TileIndex::try_from(*t).is_ok_and(|index| index.is_terminal_or_honour()))
+    }
+
+    pub fn discard_called(&mut self) {
+      self.discard_called = true;
+    }
+
+    pub fn is_closed(&self) -> bool {
+      self.melds.iter().all(|m| !m.is_open())
+    }
+
+    // Tiles handed out before play starts, which the player isn't asked about
+    pub fn deal(&mut self, tiles: &[MahjongTile]) {
+      self.hand.extend_from_slice(tiles);
+      self.hand_counts.extend(tiles.iter().copied());
+    }
+
+    pub fn turn(&mut self, tile: MahjongTile, table: &TableView) -> TurnState {
+      self.hand.push(tile);
+      self.hand_counts.add(tile);
+      self.player.turn(&self.hand, table)
+    }
+
+    // The discard after a chi or pon, which comes without a draw
+    pub fn turn_after_call(&mut self, table: &TableView) -> TurnState {
+      self.player.turn(&self.hand, table)
+    }
+
+    pub fn offer_discard(&self, tile: MahjongTile, options: &[DiscardResponse], table: &TableView) -> DiscardResponse {
+      self.player.offer_discard(&self.hand, tile, options, table)
     }
 
+    // Every claim the hand can make on another player's discard. Ron isn't
+    // open to a furiten hand, chi is only open to the next seat, and no melds
+    // can be called off the last tile. MCR has no furiten and knitted hands
+    // win too, so there ron is always offered for the score to decide.
+    pub fn call_options(&self, tile: MahjongTile, scoring: Scoring, chi: bool, melds: bool, kan: bool) -> Vec<DiscardResponse> {
+      let mut options = vec![DiscardResponse::Pass];
+      let ron = match scoring {
+        Scoring::Riichi => self.wins_with(tile) && !self.is_furiten(),
+        Scoring::Mcr => true,
+      };
+      if ron {
+        options.push(DiscardResponse::Ron);
+      }
+      if !melds {
+        return options;
+      }
+      let held = self.hand_counts.count(tile);
+      if held >= 2 {
+        options.push(DiscardResponse::Pon);
+      }
+      if held >= 3 && kan {
+        options.push(DiscardResponse::Kan);
+      }
+      if let (true, Ok(index)) = (chi, TileIndex::try_from(tile)) {
+        for (a, b) in [(-2, -1), (-1, 1), (1, 2)] {
+          if let (Some(a), Some(b)) = (index.offset(a), index.offset(b)) {
+            if self.hand_counts.contains(a) && self.hand_counts.contains(b) {
+              options.push(DiscardResponse::Chi(a.tile(), b.tile()));
+            }
+          }
+        }
+      }
+      options
+    }
+
+    // Lays down the meld made by calling `tile` from `from`. `response` must
+    // be one of the call options offered.
+    pub fn call(&mut self, tile: MahjongTile, from: Wind, response: DiscardResponse) -> Meld {
+      let called = Called { tile, from };
+      let meld = match response {
+        DiscardResponse::Pon => Meld::Pung(Pung::open([tile, self.take(tile), self.take(tile)], called)),
+        DiscardResponse::Kan => Meld::Kong(Kong::open([tile, self.take(tile), self.take(tile), self.take(tile)], called)),
+        DiscardResponse::Chi(a, b) => {
+          let mut tiles = [tile, self.take(a), self.take(b)];
+          tiles.sort_by_key(|t| TileIndex::try_from(*t).ok());
+          Meld::Chow(Chow::open(tiles, called))
+        }
+        DiscardResponse::Pass | DiscardResponse::Ron => panic!("{:?} doesn't make a meld", response),
+      };
+      self.melds.push(meld);
+      meld
+    }
+
+    // Ankan: four concealed tiles set aside as a kan
+    pub fn closed_kan(&mut self, tile: MahjongTile) -> Result<Meld, &str> {
+      if self.hand_counts.count(tile) < 4 {
+        return Err("Not holding four");
+      }
+      let meld = Meld::Kong(Kong::closed([self.take(tile), self.take(tile), self.take(tile), self.take(tile)]));
+      self.melds.push(meld);
+      Ok(meld)
+    }
+
+    // Shouminkan: the tile added to one of the player's called pungs
+    pub fn added_kan(&mut self, tile: MahjongTile) -> Result<Meld, &str> {
+      let position = self.melds.iter().position(|m| matches!(m, Meld::Pung(_)) && m.tiles()[0] == tile);
+      let (Some(position), true) = (position, self.hand_counts.contains(tile)) else {
+        return Err("No pung to add to");
+      };
+      let Meld::Pung(pung) = self.melds[position] else { unreachable!() };
+      let meld = Meld::Kong(pung.add(self.take(tile)));
+      self.melds[position] = meld;
+      Ok(meld)
+    }
+
+    // Kita: a North from the hand set aside, counting as a dora
+    pub fn extract_nukidora(&mut self) -> Result<MahjongTile, &str> {
+      let north = MahjongTile::Wind(Wind::North);
+      if !self.hand_counts.contains(north) {
+        return Err("No North to set aside");
+      }
+      let tile = self.take(north);
+      self.nukidora.push(tile);
+      Ok(tile)
+    }
+
+    // A flower goes straight out in front of the player, never into the hand
+    pub fn set_aside_flower(&mut self, tile: MahjongTile) {
+      self.flowers.push(tile);
+    }
+
+    // Moves a tile out of the hand, returning the actual tile so red fives
+    // keep track of where they are
+    fn take(&mut self, tile: MahjongTile) -> MahjongTile {
+      let position = self.hand.iter().position(|t| *t == tile).unwrap();
+      self.hand_counts.remove(tile);
+      self.hand.remove(position)
+    }
+
+    // Whether the hand as it stands is complete
+    pub fn is_complete(&self) -> bool {
+      is_agari(&self.hand_counts, &self.melds)
+    }
+
+    // Whether `tile` would complete the hand
+    pub fn wins_with(&self, tile: MahjongTile) -> bool {
+      let mut counts = self.hand_counts;
+      counts.add(tile);
+      is_agari(&counts, &self.melds)
+    }
+
+    // Throws the exact tile asked for where the hand has it, so a red five
+    // only goes when it's named
     pub fn discard(&mut self, tile: MahjongTile) -> Result<TurnState, &str> {
-      let tile_position = self.hand.iter().position(|t| *t == tile);
+      let tile_position = self.hand.iter().position(|t| *t == tile && t.is_red() == tile.is_red())
+        .or_else(|| self.hand.iter().position(|t| *t == tile));
       if let Some(tile_position) = tile_position {
-        self.hand.remove(tile_position);
+        let tile = self.hand.remove(tile_position);
+        self.hand_counts.remove(tile);
+        self.discards.push(tile);
+        self.passed_win = false;
+        self.break_ippatsu();
         Ok(TurnState::Discard(tile))
       } else {
         Err("Missing tile")
       }
     }
+
+    // Declares riichi on the way to discarding `tile`. The stick is only put
+    // down once the discard has gone by without being ronned.
+    pub fn declare_riichi(&mut self, tile: MahjongTile, double: bool) -> Result<TurnState, &str> {
+      if self.riichi.is_some() {
+        return Err("Already in riichi");
+      }
+      if !self.is_closed() {
+        return Err("Hand is open");
+      }
+      if self.points < RIICHI_STICK {
+        return Err("Not enough points");
+      }
+      let mut rest = self.hand_counts;
+      if !rest.remove(tile) || !is_tenpai(&rest, &self.melds) {
+        return Err("Not tenpai");
+      }
+      if self.discard(tile).is_err() {
+        return Err("Missing tile");
+      }
+      self.riichi = Some(Riichi { double, ippatsu: true });
+      Ok(TurnState::Riichi(tile))
+    }
+
+    pub fn set_points(&mut self, points: i32) {
+      self.points = points;
+    }
+
+    // Negative when paying out
+    pub fn add_points(&mut self, points: i32) {
+      self.points += points;
+    }
+
+    // Ippatsu lasts until the riichi player's next discard or any call
+    pub fn break_ippatsu(&mut self) {
+      if let Some(riichi) = self.riichi.as_mut() {
+        riichi.ippatsu = false;
+      }
+    }
 }
\ No newline at end of file
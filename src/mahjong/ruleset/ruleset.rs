@@ -0,0 +1,342 @@
+use std::fs;
+use std::path::Path;
+
+use crate::mahjong::game_match::MatchLength;
+use crate::mahjong::tile::enums::Wind;
+use crate::mahjong::wall::tile_set::TileSet;
+
+// Which abortive draws are played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbortiveDraws {
+    pub kyuushu_kyuuhai: bool,
+    pub suufon_renda: bool,
+    pub suucha_riichi: bool,
+    pub suukaikan: bool,
+    pub sanchahou: bool,
+}
+
+impl AbortiveDraws {
+    pub fn all() -> Self {
+        AbortiveDraws { kyuushu_kyuuhai: true, suufon_renda: true, suucha_riichi: true, suukaikan: true, sanchahou: true }
+    }
+
+    pub fn none() -> Self {
+        AbortiveDraws { kyuushu_kyuuhai: false, suufon_renda: false, suucha_riichi: false, suukaikan: false, sanchahou: false }
+    }
+}
+
+// Who pays for the seat missing from a three player tsumo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanmaTsumo {
+    // Nobody, so a tsumo is worth less than a ron (tsumo-son)
+    Loss,
+    // The two payers split it between them
+    Split,
+}
+
+// How winning hands are valued
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scoring {
+    // Yaku, han and fu, with dora
+    Riichi,
+    // The 81 fan of the Chinese Official rules, with flowers in the wall
+    Mcr,
+}
+
+// Everything that differs between the common rule sets. Defaults to Tenhou's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ruleset {
+    pub scoring: Scoring,
+    // Red fives per suit, in man, pin, sou order
+    pub red_fives: [u8; 3],
+    // Tanyao counts with an open hand
+    pub kuitan: bool,
+    // Two players can ron the same tile. Otherwise the first after the
+    // discarder takes it (atamahane).
+    pub double_ron: bool,
+    // 4 han 30 fu and 3 han 60 fu are paid as mangan
+    pub kiriage_mangan: bool,
+    // The match ends when someone goes below zero
+    pub tobi: bool,
+    pub agari_yame: bool,
+    // Han from yaku a win needs, dora not counted. Under MCR it's the fan
+    // needed, flowers not counted.
+    pub minimum_han: u32,
+    pub length: MatchLength,
+    pub west_extension: bool,
+    pub abortive_draws: AbortiveDraws,
+    // Three players, East to West, with 2-8 man taken out, no chi and North
+    // set aside as nukidora
+    pub sanma: bool,
+    pub sanma_tsumo: SanmaTsumo,
+    pub starting_points: i32,
+    // Someone has to finish the last round on this many points for the
+    // match to end there
+    pub target_points: i32,
+}
+
+impl Ruleset {
+    pub fn tenhou() -> Self {
+        Ruleset {
+            scoring: Scoring::Riichi,
+            red_fives: [1, 1, 1],
+            kuitan: true,
+            double_ron: true,
+            kiriage_mangan: false,
+            tobi: true,
+            agari_yame: true,
+            minimum_han: 1,
+            length: MatchLength::Hanchan,
+            west_extension: true,
+            abortive_draws: AbortiveDraws::all(),
+            sanma: false,
+            sanma_tsumo: SanmaTsumo::Loss,
+            starting_points: 25000,
+            target_points: 30000,
+        }
+    }
+
+    // World Riichi Championship: no red fives, no abortive draws, head bump
+    pub fn wrc() -> Self {
+        Ruleset {
+            red_fives: [0, 0, 0],
+            double_ron: false,
+            kiriage_mangan: true,
+            tobi: false,
+            agari_yame: false,
+            west_extension: false,
+            abortive_draws: AbortiveDraws::none(),
+            starting_points: 30000,
+            ..Self::tenhou()
+        }
+    }
+
+    // European Mahjong Association: like WRC, but with double ron and no
+    // kiriage
+    pub fn ema() -> Self {
+        Ruleset {
+            double_ron: true,
+            kiriage_mangan: false,
+            ..Self::wrc()
+        }
+    }
+
+    // Tenhou's three player game: red fives in pin and sou only
+    pub fn sanma() -> Self {
+        Ruleset {
+            red_fives: [0, 1, 1],
+            sanma: true,
+            starting_points: 35000,
+            target_points: 40000,
+            ..Self::tenhou()
+        }
+    }
+
+    // Chinese Official: eight fan to win, flowers, no riichi or dora, and
+    // every seat deals once in each of the four rounds. Points start at zero
+    // and move by the fan alone.
+    pub fn mcr() -> Self {
+        Ruleset {
+            scoring: Scoring::Mcr,
+            red_fives: [0, 0, 0],
+            double_ron: false,
+            tobi: false,
+            agari_yame: false,
+            minimum_han: 8,
+            length: MatchLength::Iichan,
+            west_extension: false,
+            abortive_draws: AbortiveDraws::none(),
+            starting_points: 0,
+            target_points: 0,
+            ..Self::tenhou()
+        }
+    }
+
+    pub fn tile_set(&self) -> TileSet {
+        let [man, pin, sou] = self.red_fives;
+        let mut tile_set = TileSet::standard().with_red_fives(man, pin, sou);
+        if self.sanma {
+            tile_set = tile_set.sanma();
+        }
+        match self.scoring {
+            Scoring::Mcr => tile_set.with_flowers(),
+            Scoring::Riichi => tile_set,
+        }
+    }
+
+    pub fn players(&self) -> usize {
+        match self.sanma {
+            true => 3,
+            false => 4,
+        }
+    }
+
+    // The seats in play, dealer first
+    pub fn seats(&self) -> Vec<Wind> {
+        [Wind::East, Wind::South, Wind::West, Wind::North][..self.players()].to_vec()
+    }
+
+    // One `key = value` line per rule, read back by `parse`
+    pub fn to_text(&self) -> String {
+        let [man, pin, sou] = self.red_fives;
+        let length = match self.length {
+            MatchLength::Tonpuusen => "tonpuusen",
+            MatchLength::Hanchan => "hanchan",
+            MatchLength::Iichan => "iichan",
+        };
+        let scoring = match self.scoring {
+            Scoring::Riichi => "riichi",
+            Scoring::Mcr => "mcr",
+        };
+        let draws = self.abortive_draws;
+        let sanma_tsumo = match self.sanma_tsumo {
+            SanmaTsumo::Loss => "loss",
+            SanmaTsumo::Split => "split",
+        };
+        [
+            format!("scoring = {}", scoring),
+            format!("red_fives = {} {} {}", man, pin, sou),
+            format!("kuitan = {}", self.kuitan),
+            format!("double_ron = {}", self.double_ron),
+            format!("kiriage_mangan = {}", self.kiriage_mangan),
+            format!("tobi = {}", self.tobi),
+            format!("agari_yame = {}", self.agari_yame),
+            format!("minimum_han = {}", self.minimum_han),
+            format!("length = {}", length),
+            format!("west_extension = {}", self.west_extension),
+            format!("kyuushu_kyuuhai = {}", draws.kyuushu_kyuuhai),
+            format!("suufon_renda = {}", draws.suufon_renda),
+            format!("suucha_riichi = {}", draws.suucha_riichi),
+            format!("suukaikan = {}", draws.suukaikan),
+            format!("sanchahou = {}", draws.sanchahou),
+            format!("sanma = {}", self.sanma),
+            format!("sanma_tsumo = {}", sanma_tsumo),
+            format!("starting_points = {}", self.starting_points),
+            format!("target_points = {}", self.target_points),
+        ].iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    // Reads rules written by `to_text`. Rules left out keep Tenhou's value,
+    // and `#` starts a comment.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut ruleset = Self::tenhou();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = value`", number + 1))?;
+            ruleset.set(key.trim(), value.trim()).map_err(|problem| format!("line {}: {}", number + 1, problem))?;
+        }
+        Ok(ruleset)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        Self::parse(&text)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let flag = || value.parse::<bool>().map_err(|_| format!("{} must be true or false", key));
+        let number = || value.parse::<i32>().map_err(|_| format!("{} must be a number", key));
+        match key {
+            "scoring" => {
+                self.scoring = match value {
+                    "riichi" => Scoring::Riichi,
+                    "mcr" => Scoring::Mcr,
+                    _ => return Err(format!("unknown scoring {}", value)),
+                }
+            }
+            "red_fives" => {
+                let counts: Vec<u8> = value.split_whitespace().map(|count| count.parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| "red_fives counts must be numbers".to_string())?;
+                self.red_fives = counts.try_into().map_err(|_| "red_fives takes three counts".to_string())?;
+            }
+            "kuitan" => self.kuitan = flag()?,
+            "double_ron" => self.double_ron = flag()?,
+            "kiriage_mangan" => self.kiriage_mangan = flag()?,
+            "tobi" => self.tobi = flag()?,
+            "agari_yame" => self.agari_yame = flag()?,
+            "minimum_han" => {
+                self.minimum_han = number()?.try_into().map_err(|_| "minimum_han can't be negative".to_string())?;
+            }
+            "length" => {
+                self.length = match value {
+                    "tonpuusen" => MatchLength::Tonpuusen,
+                    "hanchan" => MatchLength::Hanchan,
+                    "iichan" => MatchLength::Iichan,
+                    _ => return Err(format!("unknown length {}", value)),
+                }
+            }
+            "west_extension" => self.west_extension = flag()?,
+            "kyuushu_kyuuhai" => self.abortive_draws.kyuushu_kyuuhai = flag()?,
+            "suufon_renda" => self.abortive_draws.suufon_renda = flag()?,
+            "suucha_riichi" => self.abortive_draws.suucha_riichi = flag()?,
+            "suukaikan" => self.abortive_draws.suukaikan = flag()?,
+            "sanchahou" => self.abortive_draws.sanchahou = flag()?,
+            "sanma" => self.sanma = flag()?,
+            "sanma_tsumo" => {
+                self.sanma_tsumo = match value {
+                    "loss" => SanmaTsumo::Loss,
+                    "split" => SanmaTsumo::Split,
+                    _ => return Err(format!("unknown sanma_tsumo {}", value)),
+                }
+            }
+            "starting_points" => self.starting_points = number()?,
+            "target_points" => self.target_points = number()?,
+            _ => return Err(format!("unknown rule {}", key)),
+        }
+        Ok(())
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::tenhou()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_round_trip_through_text() {
+        let split = Ruleset { sanma_tsumo: SanmaTsumo::Split, ..Ruleset::sanma() };
+        for ruleset in [Ruleset::tenhou(), Ruleset::wrc(), Ruleset::ema(), Ruleset::sanma(), split, Ruleset::mcr()] {
+            assert_eq!(Ruleset::parse(&ruleset.to_text()), Ok(ruleset));
+        }
+        assert_eq!(Ruleset::wrc().tile_set().red_fives, [0, 0, 0]);
+        assert_eq!(Ruleset::sanma().tile_set().len(), 108);
+        assert_eq!(Ruleset::mcr().tile_set().len(), 144);
+        assert_eq!(Ruleset::sanma().seats(), vec![Wind::East, Wind::South, Wind::West]);
+    }
+
+    #[test]
+    fn parses_partial_files() {
+        let ruleset = Ruleset::parse("# Quick games\nlength = tonpuusen\n\nred_fives = 0 2 0  # two in pin\n").unwrap();
+        assert_eq!(ruleset.length, MatchLength::Tonpuusen);
+        assert_eq!(ruleset.red_fives, [0, 2, 0]);
+        assert!(ruleset.kuitan);
+        assert_eq!(Ruleset::parse("kuitan = maybe"), Err("line 1: kuitan must be true or false".to_string()));
+        assert_eq!(Ruleset::parse("riichi"), Err("line 1: expected `key = value`".to_string()));
+        assert_eq!(Ruleset::parse("oka = 20000"), Err("line 1: unknown rule oka".to_string()));
+        assert_eq!(Ruleset::parse("red_fives = 1 x 1"), Err("line 1: red_fives counts must be numbers".to_string()));
+        assert_eq!(Ruleset::parse("red_fives = 1 1"), Err("line 1: red_fives takes three counts".to_string()));
+        assert_eq!(Ruleset::parse("minimum_han = -1"), Err("line 1: minimum_han can't be negative".to_string()));
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let path = std::env::temp_dir().join(format!("ruleset-{}.txt", std::process::id()));
+        Ruleset::ema().save(&path).unwrap();
+        assert_eq!(Ruleset::load(&path), Ok(Ruleset::ema()));
+        fs::remove_file(&path).unwrap();
+    }
+}
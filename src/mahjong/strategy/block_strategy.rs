@@ -1,5 +1,6 @@
-use std::collections::HashMap;
-
+use crate::mahjong::analysis::{agari::is_agari, machi::is_tenpai, ukeire::discard_options};
+use crate::mahjong::tile::hand_counts::{HandCounts, TileCollection};
+use crate::mahjong::tile::enums::Wind;
 use crate::mahjong::tile::mahjong_tile::MahjongTile;
 
 use super::strategy::Strategy;
@@ -7,190 +8,196 @@ use super::strategy::Strategy;
 pub struct BlockStrategy {}
 
 impl Strategy for BlockStrategy {
-    fn winning(&self, hand: &Vec<MahjongTile>) -> bool {
-      get_all_blocks(hand).iter().any(|b| b.winning())
+    fn winning(&self, hand: &HandCounts, called: &[Meld]) -> bool {
+      is_agari(hand, called)
     }
 
-    fn discard(&self, hand: &Vec<MahjongTile>) -> Vec<(MahjongTile, i64)> {
-      let mut blocks = get_all_blocks(hand);
-      blocks.sort_by(|b, c| b.floating_tiles.len().cmp(&c.floating_tiles.len()));
-      if let Some(block) = blocks.first() {
-        if block.floating_tiles.len() > 0 {
-          return block.floating_tiles.iter().map(|t| (*t, 1)).collect()
-        } else if block.protoruns.len() > 0 {
-          return block.protoruns.iter().map(|p| (p.tiles, p.possible_melds().len() as i64))
-            .flat_map(|(tiles, p)| tiles.map(|t| (t, p))).collect()
-        } else {
-          return hand.iter().map(|t| (*t, 1)).collect()
-          // block.protoruns.iter().flat_map(|proto| proto.tiles).map(|t| (*t, 1)).collect()
-        };
-      }
-      vec![(*hand.first().unwrap(), 1)]
+    // Every tile in hand weighted by the shanten left after throwing it away,
+    // then by how many unseen tiles would improve what's left
+    fn discard(&self, hand: &HandCounts, called: &[Meld], visible: &HandCounts) -> Vec<(MahjongTile, i64)> {
+      discard_options(hand, called.len(), visible).iter()
+        .map(|o| (o.discard, o.shanten as i64 * 1000 - o.acceptance.total() as i64))
+        .collect()
+    }
+
+    // Always, as soon as the hand is tenpai
+    fn riichi(&self, hand: &HandCounts, called: &[Meld], discard: MahjongTile) -> bool {
+      let mut rest = *hand;
+      rest.remove(discard) && is_tenpai(&rest, called)
     }
 }
 
 impl BlockStrategy {
 }
 
-fn get_all_blocks(tiles: &Vec<MahjongTile>) -> Vec<Block> {
-  let mut blocks = Vec::new();
-
-  let mut tile_count = HashMap::new();
-  for tile in tiles.clone() {
-    tile_count
-      .entry(tile)
-      .and_modify(|count| *count += 1)
-      .or_insert(1);
-  }
-  for (tile, count) in tile_count {
-    if count >= 2 {
-      blocks.push(find_blocks_assuming_pair(tiles.clone(), tile))
-    }
-  }
+// The discard a called meld was completed with, and the seat it came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Called {
+  pub tile: MahjongTile,
+  pub from: Wind,
+}
 
-  blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chow {
+  tiles: [MahjongTile; 3],
+  called: Option<Called>,
 }
 
-fn find_blocks_assuming_pair(tiles: Vec<MahjongTile>, tile: MahjongTile) -> Block {
-  let mut tiles_without_pair = tiles.clone();
-  for _ in 0..2 {
-    let position = tiles_without_pair.iter().position(|x| *x == tile);
-    if let Some(position) = position {
-      tiles_without_pair.remove(position);
-    }
-  }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pung {
+  tiles: [MahjongTile; 3],
+  called: Option<Called>,
+}
 
-  let (melds, floating_tiles) = find_melds(tiles_without_pair);
-  let (protoruns, floating_tiles) = find_protoruns(floating_tiles);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Kong {
+  tiles: [MahjongTile; 4],
+  called: Option<Called>,
+}
 
-  Block { pair: (tile, tile), melds, protoruns, floating_tiles}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Meld {
+  Chow(Chow),
+  Pung(Pung),
+  Kong(Kong),
 }
 
-fn find_protoruns(floating_tiles: Vec<MahjongTile>) -> (Vec<Protorun>, Vec<MahjongTile>) {
-  let mut new_tiles = floating_tiles.clone();
-  for tile in floating_tiles {
-    for protorun in tile.possible_protoruns() {
-      if protorun.included(&new_tiles) {
-        protorun.remove(&mut new_tiles);
-        let (mut results, remaining) = find_protoruns(new_tiles);
-        results.push(protorun);
-        return (results, remaining)
-      }
+impl Meld {
+  pub fn tiles(&self) -> &[MahjongTile] {
+    match self {
+      Meld::Chow(chow) => &chow.tiles,
+      Meld::Pung(pung) => &pung.tiles,
+      Meld::Kong(kong) => &kong.tiles,
     }
   }
-  (Vec::new(), new_tiles)
-}
 
-fn find_melds(tiles: Vec<MahjongTile>) -> (Vec<Meld>, Vec<MahjongTile>) {
-  let mut new_tiles = tiles.clone();
-  for tile in tiles {
-    for meld in tile.possible_melds() {
-      if meld.included(&new_tiles) {
-        meld.remove(&mut new_tiles);
-        let (mut results, remaining) = find_melds(new_tiles);
-        results.push(meld);
-        return (results, remaining)
-      }
+  pub fn called(&self) -> Option<Called> {
+    match self {
+      Meld::Chow(chow) => chow.called,
+      Meld::Pung(pung) => pung.called,
+      Meld::Kong(kong) => kong.called,
     }
   }
-  (Vec::new(), new_tiles)
-}
-
-#[derive(Debug)]
-struct Block {
-  pair: (MahjongTile, MahjongTile),
-  melds: Vec<Meld>,
-  protoruns: Vec<Protorun>,
-  floating_tiles: Vec<MahjongTile>
-}
 
-impl Block {
-  pub fn winning(&self) -> bool {
-    self.melds.len() == 4
+  pub fn is_open(&self) -> bool {
+    self.called().is_some()
   }
-}
-
-#[derive(Debug, PartialEq)]
-pub struct Chow {
-  tiles: [MahjongTile; 3]
-}
-
-#[derive(Debug, PartialEq)]
-pub struct Pung {
-  tiles: [MahjongTile; 3]
-}
 
-#[derive(Debug, PartialEq)]
-pub enum Meld {
-  Chow(Chow),
-  Pung(Pung)
+  // The tiles that came out of the caller's own hand
+  pub fn from_hand(&self) -> Vec<MahjongTile> {
+    let mut tiles = self.tiles().to_vec();
+    if let Some(called) = self.called() {
+      let position = tiles.iter()
+        .position(|t| *t == called.tile && t.is_red() == called.tile.is_red())
+        .unwrap();
+      tiles.remove(position);
+    }
+    tiles
+  }
 }
 
 impl Pung {
   pub fn new(tile: MahjongTile) -> Self {
     Pung {
-      tiles: [tile; 3]
+      tiles: [tile; 3],
+      called: None,
     }
   }
+
+  pub fn open(tiles: [MahjongTile; 3], called: Called) -> Self {
+    Pung { tiles, called: Some(called) }
+  }
+
+  // Shouminkan: the fourth tile added to a called pung
+  pub fn add(&self, tile: MahjongTile) -> Kong {
+    let [a, b, c] = self.tiles;
+    Kong { tiles: [a, b, c, tile], called: self.called }
+  }
 }
 
 impl Chow {
   pub fn new(a: MahjongTile, b: MahjongTile, c: MahjongTile) -> Self {
     Chow {
-      tiles: [a, b, c]
+      tiles: [a, b, c],
+      called: None,
+    }
+  }
+
+  pub fn open(tiles: [MahjongTile; 3], called: Called) -> Self {
+    Chow { tiles, called: Some(called) }
+  }
+}
+
+impl Kong {
+  pub fn new(tile: MahjongTile) -> Self {
+    Kong {
+      tiles: [tile; 4],
+      called: None,
     }
   }
+
+  // Ankan, declared from four tiles in hand
+  pub fn closed(tiles: [MahjongTile; 4]) -> Self {
+    Kong { tiles, called: None }
+  }
+
+  pub fn open(tiles: [MahjongTile; 4], called: Called) -> Self {
+    Kong { tiles, called: Some(called) }
+  }
 }
 
-trait Removeable {
-  fn remove(&self, tiles: &mut Vec<MahjongTile>);
-  fn included(&self, tiles: &Vec<MahjongTile>) -> bool;
+pub trait Removeable {
+  // Takes whichever of the tiles it can out of `tiles`, false if any were
+  // missing
+  fn remove<T: TileCollection>(&self, tiles: &mut T) -> bool;
+  fn included<T: TileCollection>(&self, tiles: &T) -> bool;
 }
 
 impl Removeable for Meld {
-    fn remove(&self, tiles: &mut Vec<MahjongTile>) {
+    fn remove<T: TileCollection>(&self, tiles: &mut T) -> bool {
        match self {
           Meld::Chow(chow) => chow.remove(tiles),
           Meld::Pung(pung) => pung.remove(tiles),
-       };
+          Meld::Kong(kong) => kong.remove(tiles),
+       }
     }
 
-    fn included(&self, tiles: &Vec<MahjongTile>) -> bool {
+    fn included<T: TileCollection>(&self, tiles: &T) -> bool {
        match self {
           Meld::Chow(chow) => chow.included(tiles),
           Meld::Pung(pung) => pung.included(tiles),
+          Meld::Kong(kong) => kong.included(tiles),
        }
     }
 }
 
 impl Removeable for Pung {
-    fn remove(&self, tiles: &mut Vec<MahjongTile>) {
-      for tile in self.tiles {
-        let position = tiles.iter().position(|x| *x == tile);
-        if let Some(position) = position {
-          tiles.remove(position);
-        }
-      }
+    fn remove<T: TileCollection>(&self, tiles: &mut T) -> bool {
+      !self.tiles.map(|tile| tiles.take(tile)).contains(&false)
+    }
+
+    fn included<T: TileCollection>(&self, tiles: &T) -> bool {
+      tiles.count_of(self.tiles[0]) > 2
     }
+}
 
-    fn included(&self, tiles: &Vec<MahjongTile>) -> bool {
-      tiles.iter().filter(|t| **t == self.tiles[0]).count() > 2
+impl Removeable for Kong {
+    fn remove<T: TileCollection>(&self, tiles: &mut T) -> bool {
+      !self.tiles.map(|tile| tiles.take(tile)).contains(&false)
+    }
+
+    fn included<T: TileCollection>(&self, tiles: &T) -> bool {
+      tiles.count_of(self.tiles[0]) > 3
     }
 }
 
 impl Removeable for Chow {
-    fn remove(&self, tiles: &mut Vec<MahjongTile>) {
-      for tile in self.tiles {
-        let position = tiles.iter().position(|t| *t == tile);
-        match position {
-          Some(position) => { tiles.remove(position); }
-          None => { println!("PROBLEM") }
-        }
-      };
+    fn remove<T: TileCollection>(&self, tiles: &mut T) -> bool {
+      !self.tiles.map(|tile| tiles.take(tile)).contains(&false)
     }
 
-    fn included(&self, tiles: &Vec<MahjongTile>) -> bool {
-      self.tiles.iter().all(|self_tile| tiles.contains(self_tile))
+    fn included<T: TileCollection>(&self, tiles: &T) -> bool {
+      self.tiles.iter().all(|self_tile| tiles.count_of(*self_tile) > 0)
     }
 }
 
@@ -200,7 +207,7 @@ pub struct Protorun {
 }
 
 impl Protorun {
-  fn possible_melds(&self) -> Vec<Meld> {
+  pub fn possible_melds(&self) -> Vec<Meld> {
     let [a, b] = self.tiles;
     let a_melds = a.possible_melds();
     let b_melds = b.possible_melds();
@@ -215,36 +222,28 @@ impl Protorun {
 }
 
 impl Removeable for Protorun {
-    fn remove(&self, tiles: &mut Vec<MahjongTile>) {
+    fn remove<T: TileCollection>(&self, tiles: &mut T) -> bool {
       if !self.included(tiles) {
-        println!("WTF?!");
-        return
+        return false
       }
-      for tile in self.tiles {
-        let position = tiles.iter().position(|t| *t == tile);
-        match position {
-          Some(position) => { tiles.remove(position); }
-          None => { println!("PROBLEM") }
-        }
-      };
+      !self.tiles.map(|tile| tiles.take(tile)).contains(&false)
     }
 
-    fn included(&self, tiles: &Vec<MahjongTile>) -> bool {
+    fn included<T: TileCollection>(&self, tiles: &T) -> bool {
       let [a, b] = self.tiles;
       if a == b {
-        tiles.iter().filter(|t| **t == self.tiles[0]).count() > 1
+        tiles.count_of(a) > 1
       } else {
-        self.tiles.iter().all(|self_tile| tiles.contains(self_tile))
+        self.tiles.iter().all(|self_tile| tiles.count_of(*self_tile) > 0)
       }
     }
 }
 
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::useless_conversion, clippy::clone_on_copy)]
 mod tests {
-  use strum::IntoEnumIterator;
-
-  use crate::mahjong::tile::{mahjong_tile::MahjongTile, enums::{Wind, Suit, Dragon}};
+  use crate::mahjong::tile::{mahjong_tile::MahjongTile, enums::{Suit, Dragon}, notation::parse_tiles};
 
   use super::*;
 
@@ -252,10 +251,6 @@ mod tests {
     (start..start+3).into_iter().map(|v| MahjongTile::new_suit(suit, v)).collect()
   }
 
-  fn gen_pung(tile: MahjongTile) -> Vec<MahjongTile> {
-    (0..3).into_iter().map(|_| tile.clone() ).collect()
-  }
-
   fn gen_x_copies(tile: MahjongTile, x: i32) -> Vec<MahjongTile> {
     (0..x).into_iter().map(|_| tile.clone() ).collect()
   }
@@ -271,7 +266,7 @@ mod tests {
     hand.extend(gen_x_copies(MahjongTile::Dragon(Dragon::Red), 2));
 
     assert_eq!(
-      strategy.winning(&hand),
+      strategy.winning(&HandCounts::from_tiles(&hand), &[]),
       true
     );
     let strategy = BlockStrategy{};
@@ -284,11 +279,48 @@ mod tests {
     hand.push(MahjongTile::new_suit(Suit::Pin, 6));
     hand.push(MahjongTile::new_suit(Suit::Pin, 7));
     assert_eq!(
-      strategy.winning(&hand),
+      strategy.winning(&HandCounts::from_tiles(&hand), &[]),
       false
     )
   }
 
+  #[test]
+  fn discards_towards_tenpai() {
+    let strategy = BlockStrategy{};
+    let hand = HandCounts::from_tiles(&parse_tiles("123789m345s77z469p").unwrap());
+    let mut discards = strategy.discard(&hand, &[], &hand);
+    discards.sort_by_key(|(_, weight)| *weight);
+    assert_eq!(discards.first().map(|(tile, _)| tile.to_string()), Some("9p".to_string()));
+    assert!(strategy.riichi(&hand, &[], "9p".parse().unwrap()));
+    assert!(!strategy.riichi(&hand, &[], "7z".parse().unwrap()));
+  }
+
+  #[test]
+  fn called_melds() {
+    let five: MahjongTile = "5p".parse().unwrap();
+    let pung = Meld::Pung(Pung::open([MahjongTile::new_red(Suit::Pin), five, five], Called { tile: five, from: Wind::West }));
+    assert!(pung.is_open());
+    assert_eq!(pung.from_hand().iter().filter(|t| t.is_red()).count(), 1);
+    assert!(!Meld::Pung(Pung::new(five)).is_open());
+
+    let strategy = BlockStrategy{};
+    let hand = HandCounts::from_tiles(&parse_tiles("789m345s77z222p").unwrap());
+    assert!(strategy.winning(&hand, &[pung]));
+  }
+
+  #[test]
+  fn removing_reports_missing_tiles() {
+    let chow = Chow::new(
+      MahjongTile::new_suit(Suit::Man, 1),
+      MahjongTile::new_suit(Suit::Man, 2),
+      MahjongTile::new_suit(Suit::Man, 3),
+    );
+    let mut tiles = parse_tiles("123m5p").unwrap();
+    assert!(chow.remove(&mut tiles));
+    assert_eq!(tiles, parse_tiles("5p").unwrap());
+    assert!(!chow.remove(&mut tiles));
+  }
+
   #[test]
   fn chow_included() {
     let tiles = vec![
@@ -336,4 +368,4 @@ mod tests {
       ]}.included(&tiles), false
     )
   }
-}
\ No newline at end of file
+}
@@ -1,16 +1,20 @@
-use crate::mahjong::tile::{mahjong_tile::MahjongTile};
+use crate::mahjong::tile::{hand_counts::HandCounts, mahjong_tile::MahjongTile};
 
-use super::strategy::Strategy;
+use super::{block_strategy::Meld, strategy::Strategy};
 
 
 pub struct DumbStrategy;
 
 impl Strategy for DumbStrategy {
-    fn discard(&self, tiles: &Vec<MahjongTile>) -> Vec<(MahjongTile, i64)> {
-      vec![(*tiles.last().unwrap(), 1)]
+    fn discard(&self, tiles: &HandCounts, _called: &[Meld], _visible: &HandCounts) -> Vec<(MahjongTile, i64)> {
+      vec![(tiles.iter().last().unwrap().0.tile(), 1)]
     }
 
-    fn winning(&self, _hand: &Vec<MahjongTile>) -> bool {
+    fn winning(&self, _hand: &HandCounts, _called: &[Meld]) -> bool {
       false
     }
-}
\ No newline at end of file
+
+    fn riichi(&self, _hand: &HandCounts, _called: &[Meld], _discard: MahjongTile) -> bool {
+      false
+    }
+}
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod strategy;
 pub mod dumb_strategy;
 pub mod block_strategy;
\ No newline at end of file
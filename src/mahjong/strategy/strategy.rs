@@ -1,6 +1,13 @@
-use crate::mahjong::tile::mahjong_tile::MahjongTile;
+use crate::mahjong::tile::{hand_counts::HandCounts, mahjong_tile::MahjongTile};
 
+use super::block_strategy::Meld;
+
+// `hand` is always the concealed part of the hand, `called` the melds already
+// laid down
 pub trait Strategy {
-  fn winning(&self, hand: &Vec<MahjongTile>) -> bool;
-  fn discard(&self, hand: &Vec<MahjongTile>) -> Vec<(MahjongTile, i64)>;
-}
\ No newline at end of file
+  fn winning(&self, hand: &HandCounts, called: &[Meld]) -> bool;
+  // `visible` counts every tile the player can see, their own hand included
+  fn discard(&self, hand: &HandCounts, called: &[Meld], visible: &HandCounts) -> Vec<(MahjongTile, i64)>;
+  // Whether to declare riichi when throwing `discard` from a closed hand
+  fn riichi(&self, hand: &HandCounts, called: &[Meld], discard: MahjongTile) -> bool;
+}
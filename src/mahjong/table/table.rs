@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use strum::IntoEnumIterator;
+
+use crate::mahjong::player_state::player_state::STARTING_POINTS;
+use crate::mahjong::tile::enums::Wind;
+
+// Each repeat counter adds this to a win, split between the payers on a tsumo
+pub const HONBA: u32 = 300;
+
+// Split between the seats that aren't tenpai at an exhaustive draw
+pub const NOTEN_PENALTY: u32 = 3000;
+
+// The same with three players
+pub const SANMA_NOTEN_PENALTY: u32 = 2000;
+
+// What carries over from one hand to the next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableState {
+    pub round_wind: Wind,
+    pub points: HashMap<Wind, i32>,
+    // Riichi deposits nobody has won yet
+    pub riichi_sticks: u32,
+    // Repeat counters, from dealer wins and draws
+    pub honba: u32,
+}
+
+impl TableState {
+    pub fn new() -> Self {
+        Self::with_starting_points(STARTING_POINTS)
+    }
+
+    pub fn with_starting_points(points: i32) -> Self {
+        TableState {
+            round_wind: Wind::East,
+            points: Wind::iter().map(|wind| (wind, points)).collect(),
+            riichi_sticks: 0,
+            honba: 0,
+        }
+    }
+
+    pub fn points(&self, seat: Wind) -> i32 {
+        self.points.get(&seat).copied().unwrap_or(STARTING_POINTS)
+    }
+}
+
+impl Default for TableState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Ron,
+    Tsumo,
+    Honba,
+    // A riichi declaration putting a stick on the table
+    RiichiDeposit,
+    // The winner taking the sticks on the table
+    RiichiSticks,
+    // Paid to the tenpai seats at an exhaustive draw
+    Noten,
+    // Paid like a mangan tsumo at an exhaustive draw
+    NagashiMangan,
+}
+
+// Points moving between seats. `None` is the table itself, which holds the
+// riichi sticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: Option<Wind>,
+    pub to: Option<Wind>,
+    pub points: u32,
+    pub reason: Reason,
+}
+
+// Every transfer made during a hand, in the order they happened
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settlement {
+    pub transfers: Vec<Transfer>,
+}
+
+impl Settlement {
+    pub fn record(&mut self, from: Option<Wind>, to: Option<Wind>, points: u32, reason: Reason) {
+        if points > 0 {
+            self.transfers.push(Transfer { from, to, points, reason });
+        }
+    }
+
+    // What the hand gained or cost `seat`
+    pub fn net(&self, seat: Wind) -> i32 {
+        self.transfers.iter()
+            .map(|t| match (t.from == Some(seat), t.to == Some(seat)) {
+                (true, false) => -(t.points as i32),
+                (false, true) => t.points as i32,
+                _ => 0,
+            })
+            .sum()
+    }
+}
@@ -1,4 +1,3 @@
-use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::mahjong::{strategy::block_strategy::{Pung, Meld, Protorun}};
@@ -41,12 +40,13 @@ pub enum Dragon {
     Red,
 }
 
+// Dora order: White, Green, Red
 impl Nextable for Dragon {
     fn next(&self) -> Dragon {
         match self {
-            Dragon::Green => Dragon::White,
-            Dragon::White => Dragon::Red,
-            Dragon::Red => Dragon::Green,
+            Dragon::White => Dragon::Green,
+            Dragon::Green => Dragon::Red,
+            Dragon::Red => Dragon::White,
         }
     }
 }
@@ -67,4 +67,24 @@ pub enum Suit {
     Pin,
     Man,
     Sou,
-}
\ No newline at end of file
+}
+
+// Flowers and seasons are bonus tiles: they never form part of a hand and
+// are set aside for a replacement draw in the rule sets that use them
+#[derive(EnumIter, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Flower {
+    Plum,
+    Orchid,
+    Chrysanthemum,
+    Bamboo,
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Flower {
+    pub fn is_season(&self) -> bool {
+        matches!(self, Flower::Spring | Flower::Summer | Flower::Autumn | Flower::Winter)
+    }
+}
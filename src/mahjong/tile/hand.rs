@@ -0,0 +1,17 @@
+use crate::mahjong::strategy::block_strategy::Meld;
+
+use super::mahjong_tile::MahjongTile;
+
+// A hand as written down: the concealed tiles plus any melds called from
+// other players' discards
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hand {
+    pub tiles: Vec<MahjongTile>,
+    pub melds: Vec<Meld>,
+}
+
+impl Hand {
+    pub fn new(tiles: Vec<MahjongTile>, melds: Vec<Meld>) -> Self {
+        Hand { tiles, melds }
+    }
+}
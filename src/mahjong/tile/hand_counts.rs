@@ -0,0 +1,170 @@
+use super::mahjong_tile::MahjongTile;
+use super::tile_index::TileIndex;
+
+// How many of each tile kind a hand holds, indexed by `TileIndex`. It is a
+// plain array so it can be copied and edited freely during hand analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandCounts([u8; TileIndex::COUNT]);
+
+impl HandCounts {
+    pub fn new() -> Self {
+        HandCounts([0; TileIndex::COUNT])
+    }
+
+    pub fn from_tiles(tiles: &[MahjongTile]) -> Self {
+        tiles.iter().copied().collect()
+    }
+
+    // Flowers have no index, so a hand never holds any
+    pub fn count(&self, tile: impl TryInto<TileIndex>) -> u8 {
+        match tile.try_into() {
+            Ok(index) => self.0[index.as_usize()],
+            Err(_) => 0,
+        }
+    }
+
+    pub fn contains(&self, tile: impl TryInto<TileIndex>) -> bool {
+        self.count(tile) > 0
+    }
+
+    // Returns false, leaving the counts untouched, for a flower
+    pub fn add(&mut self, tile: impl TryInto<TileIndex>) -> bool {
+        match tile.try_into() {
+            Ok(index) => {
+                self.0[index.as_usize()] += 1;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    // Returns false, leaving the counts untouched, if the tile isn't held
+    pub fn remove(&mut self, tile: impl TryInto<TileIndex>) -> bool {
+        let Ok(index) = tile.try_into() else {
+            return false;
+        };
+        let slot = &mut self.0[index.as_usize()];
+        match *slot {
+            0 => false,
+            _ => {
+                *slot -= 1;
+                true
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|c| *c as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|c| *c == 0)
+    }
+
+    pub fn as_array(&self) -> &[u8; TileIndex::COUNT] {
+        &self.0
+    }
+
+    // Tile kinds present in the hand along with how many copies are held
+    pub fn iter(&self) -> impl Iterator<Item = (TileIndex, u8)> + '_ {
+        TileIndex::all()
+            .map(|index| (index, self.0[index.as_usize()]))
+            .filter(|(_, count)| *count > 0)
+    }
+
+    pub fn tiles(&self) -> Vec<MahjongTile> {
+        self.iter()
+            .flat_map(|(index, count)| (0..count).map(move |_| index.tile()))
+            .collect()
+    }
+}
+
+impl Default for HandCounts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromIterator<MahjongTile> for HandCounts {
+    fn from_iter<I: IntoIterator<Item = MahjongTile>>(iter: I) -> Self {
+        let mut counts = HandCounts::new();
+        for tile in iter {
+            counts.add(tile);
+        }
+        counts
+    }
+}
+
+impl Extend<MahjongTile> for HandCounts {
+    fn extend<I: IntoIterator<Item = MahjongTile>>(&mut self, iter: I) {
+        for tile in iter {
+            self.add(tile);
+        }
+    }
+}
+
+// Anything tiles can be counted in and taken out of, so meld matching works
+// the same on a plain list of tiles and on `HandCounts`
+pub trait TileCollection {
+    fn count_of(&self, tile: MahjongTile) -> usize;
+    fn take(&mut self, tile: MahjongTile) -> bool;
+}
+
+impl TileCollection for HandCounts {
+    fn count_of(&self, tile: MahjongTile) -> usize {
+        self.count(tile) as usize
+    }
+
+    fn take(&mut self, tile: MahjongTile) -> bool {
+        self.remove(tile)
+    }
+}
+
+impl TileCollection for Vec<MahjongTile> {
+    fn count_of(&self, tile: MahjongTile) -> usize {
+        self.iter().filter(|t| **t == tile).count()
+    }
+
+    fn take(&mut self, tile: MahjongTile) -> bool {
+        match self.iter().position(|t| *t == tile) {
+            Some(position) => {
+                self.remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mahjong::tile::{enums::Flower, notation::parse_tiles};
+
+    use super::*;
+
+    #[test]
+    fn adds_and_removes() {
+        let mut counts = HandCounts::from_tiles(&parse_tiles("1123m0p5p77z").unwrap());
+        let one_man: MahjongTile = "1m".parse().unwrap();
+        assert_eq!(counts.len(), 8);
+        assert_eq!(counts.count(one_man), 2);
+        assert_eq!(counts.count("5p".parse::<MahjongTile>().unwrap()), 2);
+        assert!(counts.remove(one_man));
+        assert!(counts.remove(one_man));
+        assert!(!counts.remove(one_man));
+        assert!(!counts.contains(one_man));
+        assert_eq!(counts.len(), 6);
+        assert_eq!(counts.tiles(), parse_tiles("23m55p77z").unwrap());
+    }
+
+    #[test]
+    fn flowers_are_never_counted() {
+        let plum = MahjongTile::Flower(Flower::Plum);
+        let mut counts = HandCounts::from_tiles(&[plum, "1m".parse().unwrap()]);
+        assert_eq!(counts.len(), 1);
+        assert!(!counts.add(plum));
+        assert_eq!(counts.count(plum), 0);
+        assert!(!counts.contains(plum));
+        assert!(!counts.remove(plum));
+    }
+}
@@ -1,9 +1,12 @@
 use crate::mahjong::strategy::block_strategy::Meld;
 use crate::mahjong::strategy::block_strategy::Protorun;
+use crate::mahjong::strategy::block_strategy::Removeable;
 
 use super::enums::Dragon;
+use super::enums::Flower;
 use super::enums::Suit;
 use super::enums::Wind;
+use super::hand_counts::HandCounts;
 use super::suited_tile::SuitedTile;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -11,6 +14,7 @@ pub enum MahjongTile {
     Suit(SuitedTile),
     Dragon(Dragon),
     Wind(Wind),
+    Flower(Flower),
 }
 
 pub trait Nextable {
@@ -20,31 +24,50 @@ pub trait Nextable {
 pub trait Meldable where Self: Sized {
     fn melds(&self) -> Vec<Meld>;
     fn protoruns(&self) -> Vec<Protorun>;
-}
 
-impl MahjongTile {
-    pub fn new_suit(suit: Suit, value: i8) -> Self {
-        MahjongTile::Suit(
-            SuitedTile {
-                suit,
-                value
-            }
-        )
+    fn melds_in(&self, hand: &HandCounts) -> Vec<Meld> {
+        self.melds().into_iter().filter(|m| m.included(hand)).collect()
     }
 
+    fn protoruns_in(&self, hand: &HandCounts) -> Vec<Protorun> {
+        self.protoruns().into_iter().filter(|p| p.included(hand)).collect()
+    }
+}
+
+impl Nextable for MahjongTile {
     fn next(&self) -> Self {
         match self {
             MahjongTile::Suit(suit) => MahjongTile::Suit(suit.next()),
             MahjongTile::Dragon(dragon) => MahjongTile::Dragon( dragon.next()),
             MahjongTile::Wind(wind) => MahjongTile::Wind(wind.next()),
+            MahjongTile::Flower(flower) => MahjongTile::Flower(*flower),
         }
     }
+}
+
+impl MahjongTile {
+    pub fn new_suit(suit: Suit, value: i8) -> Self {
+        MahjongTile::Suit(SuitedTile::new(suit, value))
+    }
+
+    pub fn new_red(suit: Suit) -> Self {
+        MahjongTile::Suit(SuitedTile::new_red(suit))
+    }
+
+    pub fn is_red(&self) -> bool {
+        matches!(self, MahjongTile::Suit(SuitedTile { red: true, .. }))
+    }
+
+    pub fn is_flower(&self) -> bool {
+        matches!(self, MahjongTile::Flower(_))
+    }
 
     pub fn possible_melds(&self) -> Vec<Meld> {
         match self {
             MahjongTile::Suit(suit) => suit.melds(),
             MahjongTile::Dragon(dragon) => dragon.melds(),
             MahjongTile::Wind(wind) => wind.melds(),
+            MahjongTile::Flower(_) => Vec::new(),
         }
     }
 
@@ -53,6 +76,7 @@ impl MahjongTile {
             MahjongTile::Suit(suit) => suit.protoruns(),
             MahjongTile::Dragon(dragon) => dragon.protoruns(),
             MahjongTile::Wind(wind) => wind.protoruns(),
+            MahjongTile::Flower(_) => Vec::new(),
         }
     }
-}
\ No newline at end of file
+}
@@ -1,3 +1,7 @@
 pub mod enums;
+pub mod hand;
+pub mod hand_counts;
 pub mod mahjong_tile;
-pub mod suited_tile;
\ No newline at end of file
+pub mod notation;
+pub mod suited_tile;
+pub mod tile_index;
@@ -0,0 +1,325 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::mahjong::strategy::block_strategy::{Called, Chow, Kong, Meld, Pung};
+
+use super::enums::{Dragon, Flower, Suit, Wind};
+use super::hand::Hand;
+use super::mahjong_tile::MahjongTile;
+use super::suited_tile::SuitedTile;
+
+// Compact "MPSZ" notation: digits followed by the suit they belong to, so
+// `123m456p789s11z` is a hand, `0m` is a red five and `1234567z` are the honours
+// (East, South, West, North, White, Green, Red). Called melds go in brackets
+// after the concealed tiles, e.g. `123m456p11z[789s][555z]`, with the called
+// tile written first. Notation has no seats, so the hand is taken to be the
+// dealer's and called tiles to come from North on their left. Closed kans go
+// in parentheses, `(1111z)`. Flowers and seasons, where used, are `1234f` and
+// `5678f`.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseTileError {
+    UnexpectedCharacter { position: usize, character: char },
+    InvalidTile { position: usize, character: char, suit: char },
+    MissingSuit { position: usize },
+    UnclosedMeld { position: usize },
+    InvalidMeld { position: usize },
+    NotSingleTile,
+}
+
+impl fmt::Display for ParseTileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseTileError::UnexpectedCharacter { position, character } =>
+                write!(f, "unexpected character '{}' at position {}", character, position),
+            ParseTileError::InvalidTile { position, character, suit } =>
+                write!(f, "'{}' at position {} is not a valid {} tile", character, position, suit),
+            ParseTileError::MissingSuit { position } =>
+                write!(f, "tiles ending at position {} have no suit", position),
+            ParseTileError::UnclosedMeld { position } =>
+                write!(f, "meld opened at position {} is never closed", position),
+            ParseTileError::InvalidMeld { position } =>
+                write!(f, "meld at position {} is not a chow, pung or kan", position),
+            ParseTileError::NotSingleTile =>
+                write!(f, "expected exactly one tile"),
+        }
+    }
+}
+
+impl std::error::Error for ParseTileError {}
+
+pub fn parse_tiles(notation: &str) -> Result<Vec<MahjongTile>, ParseTileError> {
+    let hand = parse_hand(notation)?;
+    match hand.melds.first() {
+        Some(_) => {
+            let (position, character) = notation.chars().enumerate()
+                .find(|(_, c)| *c == '[' || *c == '(')
+                .unwrap_or((0, '['));
+            Err(ParseTileError::UnexpectedCharacter { position, character })
+        }
+        None => Ok(hand.tiles),
+    }
+}
+
+pub fn parse_hand(notation: &str) -> Result<Hand, ParseTileError> {
+    let mut hand = Hand::default();
+    let mut pending: Vec<(usize, char)> = Vec::new();
+    // Where the meld being read started, whether it's open, and its tiles
+    let mut meld: Option<(usize, bool, Vec<MahjongTile>)> = None;
+
+    for (position, character) in notation.chars().enumerate() {
+        match character {
+            '0'..='9' => pending.push((position, character)),
+            'm' | 'p' | 's' | 'z' | 'f' => {
+                if pending.is_empty() {
+                    return Err(ParseTileError::UnexpectedCharacter { position, character });
+                }
+                let tiles = pending.drain(..)
+                    .map(|(position, digit)| tile_from_chars(position, digit, character))
+                    .collect::<Result<Vec<MahjongTile>, ParseTileError>>()?;
+                match meld.as_mut() {
+                    Some((_, _, meld_tiles)) => meld_tiles.extend(tiles),
+                    None => hand.tiles.extend(tiles),
+                }
+            }
+            '[' | '(' if meld.is_none() && pending.is_empty() => meld = Some((position, character == '[', Vec::new())),
+            ']' | ')' if pending.is_empty() => match meld.take() {
+                Some((start, open, tiles)) if open == (character == ']') =>
+                    hand.melds.push(meld_from_tiles(start, open, &tiles)?),
+                _ => return Err(ParseTileError::UnexpectedCharacter { position, character }),
+            },
+            ' ' if pending.is_empty() => (),
+            _ => return Err(ParseTileError::UnexpectedCharacter { position, character }),
+        }
+        if meld.is_none() && !hand.melds.is_empty() && !pending.is_empty() {
+            // Concealed tiles must all come before the first called meld
+            return Err(ParseTileError::UnexpectedCharacter { position, character });
+        }
+    }
+
+    if let Some((position, _)) = pending.last() {
+        return Err(ParseTileError::MissingSuit { position: *position });
+    }
+    if let Some((position, _, _)) = meld {
+        return Err(ParseTileError::UnclosedMeld { position });
+    }
+    Ok(hand)
+}
+
+fn tile_from_chars(position: usize, digit: char, suit: char) -> Result<MahjongTile, ParseTileError> {
+    let invalid = ParseTileError::InvalidTile { position, character: digit, suit };
+    let value = digit.to_digit(10).ok_or(invalid.clone())? as i8;
+    let suit = match suit {
+        'm' => Suit::Man,
+        'p' => Suit::Pin,
+        's' => Suit::Sou,
+        'f' => return flower_from_value(value).ok_or(invalid),
+        _ => return honour_from_value(value).ok_or(invalid),
+    };
+    match value {
+        0 => Ok(MahjongTile::new_red(suit)),
+        _ => Ok(MahjongTile::new_suit(suit, value)),
+    }
+}
+
+fn honour_from_value(value: i8) -> Option<MahjongTile> {
+    match value {
+        1 => Some(MahjongTile::Wind(Wind::East)),
+        2 => Some(MahjongTile::Wind(Wind::South)),
+        3 => Some(MahjongTile::Wind(Wind::West)),
+        4 => Some(MahjongTile::Wind(Wind::North)),
+        5 => Some(MahjongTile::Dragon(Dragon::White)),
+        6 => Some(MahjongTile::Dragon(Dragon::Green)),
+        7 => Some(MahjongTile::Dragon(Dragon::Red)),
+        _ => None,
+    }
+}
+
+fn flower_from_value(value: i8) -> Option<MahjongTile> {
+    match value {
+        1 => Some(MahjongTile::Flower(Flower::Plum)),
+        2 => Some(MahjongTile::Flower(Flower::Orchid)),
+        3 => Some(MahjongTile::Flower(Flower::Chrysanthemum)),
+        4 => Some(MahjongTile::Flower(Flower::Bamboo)),
+        5 => Some(MahjongTile::Flower(Flower::Spring)),
+        6 => Some(MahjongTile::Flower(Flower::Summer)),
+        7 => Some(MahjongTile::Flower(Flower::Autumn)),
+        8 => Some(MahjongTile::Flower(Flower::Winter)),
+        _ => None,
+    }
+}
+
+fn meld_from_tiles(position: usize, open: bool, tiles: &[MahjongTile]) -> Result<Meld, ParseTileError> {
+    let invalid = ParseTileError::InvalidMeld { position };
+    let called = Called { tile: *tiles.first().ok_or(invalid.clone())?, from: Wind::North };
+    match (tiles, open) {
+        ([a, b, c, d], _) if a == b && b == c && c == d => return Ok(Meld::Kong(match open {
+            true => Kong::open([*a, *b, *c, *d], called),
+            false => Kong::closed([*a, *b, *c, *d]),
+        })),
+        ([a, b, c], true) if a == b && b == c => return Ok(Meld::Pung(Pung::open([*a, *b, *c], called))),
+        ([_, _, _], true) => (),
+        _ => return Err(invalid),
+    }
+    let mut sorted = [tiles[0], tiles[1], tiles[2]];
+    sorted.sort_by_key(sort_key);
+    match sorted {
+        [MahjongTile::Suit(x), MahjongTile::Suit(y), MahjongTile::Suit(z)]
+            if x.suit == y.suit && y.suit == z.suit && x.value + 1 == y.value && y.value + 1 == z.value =>
+                Ok(Meld::Chow(Chow::open(sorted, called))),
+        _ => Err(invalid),
+    }
+}
+
+fn sort_key(tile: &MahjongTile) -> (char, i8) {
+    (suit_char(tile), digit_value(tile))
+}
+
+fn suit_char(tile: &MahjongTile) -> char {
+    match tile {
+        MahjongTile::Suit(SuitedTile { suit: Suit::Man, .. }) => 'm',
+        MahjongTile::Suit(SuitedTile { suit: Suit::Pin, .. }) => 'p',
+        MahjongTile::Suit(SuitedTile { suit: Suit::Sou, .. }) => 's',
+        MahjongTile::Wind(_) | MahjongTile::Dragon(_) => 'z',
+        MahjongTile::Flower(_) => 'f',
+    }
+}
+
+fn digit_value(tile: &MahjongTile) -> i8 {
+    match tile {
+        MahjongTile::Suit(suited) => suited.value,
+        MahjongTile::Wind(Wind::East) => 1,
+        MahjongTile::Wind(Wind::South) => 2,
+        MahjongTile::Wind(Wind::West) => 3,
+        MahjongTile::Wind(Wind::North) => 4,
+        MahjongTile::Dragon(Dragon::White) => 5,
+        MahjongTile::Dragon(Dragon::Green) => 6,
+        MahjongTile::Dragon(Dragon::Red) => 7,
+        MahjongTile::Flower(flower) => *flower as i8 + 1,
+    }
+}
+
+fn digit_char(tile: &MahjongTile) -> char {
+    match tile.is_red() {
+        true => '0',
+        false => (b'0' + digit_value(tile) as u8) as char,
+    }
+}
+
+// Writes tiles in the order given, only emitting a suit letter when the suit
+// changes, so `format_tiles(&parse_tiles(s)?)` reproduces any compact input.
+pub fn format_tiles(tiles: &[MahjongTile]) -> String {
+    let mut out = String::new();
+    for (i, tile) in tiles.iter().enumerate() {
+        out.push(digit_char(tile));
+        if tiles.get(i + 1).map(suit_char) != Some(suit_char(tile)) {
+            out.push(suit_char(tile));
+        }
+    }
+    out
+}
+
+impl FromStr for MahjongTile {
+    type Err = ParseTileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_tiles(s)?.as_slice() {
+            [tile] => Ok(*tile),
+            _ => Err(ParseTileError::NotSingleTile),
+        }
+    }
+}
+
+impl fmt::Display for MahjongTile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", digit_char(self), suit_char(self))
+    }
+}
+
+impl FromStr for Hand {
+    type Err = ParseTileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_hand(s)
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_tiles(&self.tiles))?;
+        for meld in &self.melds {
+            match meld.called() {
+                // Called tile first, as it was read
+                Some(called) => {
+                    let mut tiles = meld.from_hand();
+                    tiles.insert(0, called.tile);
+                    write!(f, "[{}]", format_tiles(&tiles))?
+                }
+                None => write!(f, "({})", format_tiles(meld.tiles()))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_tiles() {
+        assert_eq!("5m".parse::<MahjongTile>(), Ok(MahjongTile::new_suit(Suit::Man, 5)));
+        assert_eq!("9s".parse::<MahjongTile>(), Ok(MahjongTile::new_suit(Suit::Sou, 9)));
+        assert_eq!("1z".parse::<MahjongTile>(), Ok(MahjongTile::Wind(Wind::East)));
+        assert_eq!("7z".parse::<MahjongTile>(), Ok(MahjongTile::Dragon(Dragon::Red)));
+        assert!("0p".parse::<MahjongTile>().unwrap().is_red());
+        assert_eq!("12m".parse::<MahjongTile>(), Err(ParseTileError::NotSingleTile));
+        assert_eq!(MahjongTile::Dragon(Dragon::Green).to_string(), "6z");
+        assert_eq!(MahjongTile::new_red(Suit::Sou).to_string(), "0s");
+    }
+
+    #[test]
+    fn round_trips() {
+        for notation in ["123m456p789s11z", "1234567z", "055m0p", "19m19p19s1234567z", "12345678f"] {
+            assert_eq!(format_tiles(&parse_tiles(notation).unwrap()), notation);
+        }
+        let hand: Hand = "123m456p11z[789s][555z]".parse().unwrap();
+        assert_eq!(hand.tiles.len(), 8);
+        assert_eq!(hand.melds.len(), 2);
+        assert_eq!(hand.to_string(), "123m456p11z[789s][555z]");
+        assert_eq!(parse_hand("11z[312s][645m](0555p)").unwrap().to_string(), "11z[312s][645m](0555p)");
+    }
+
+    #[test]
+    fn melds() {
+        let hand = parse_hand("[978s][222m](4444z)").unwrap();
+        let nine = MahjongTile::new_suit(Suit::Sou, 9);
+        assert_eq!(hand.melds[0], Meld::Chow(Chow::open(
+            [MahjongTile::new_suit(Suit::Sou, 7), MahjongTile::new_suit(Suit::Sou, 8), nine],
+            Called { tile: nine, from: Wind::North },
+        )));
+        assert_eq!(hand.melds[1].tiles(), [MahjongTile::new_suit(Suit::Man, 2); 3]);
+        assert!(hand.melds[1].is_open());
+        assert_eq!(hand.melds[2], Meld::Kong(Kong::closed([MahjongTile::Wind(Wind::North); 4])));
+        assert_eq!(parse_hand("[135m]"), Err(ParseTileError::InvalidMeld { position: 0 }));
+        assert_eq!(parse_hand("(555m)"), Err(ParseTileError::InvalidMeld { position: 0 }));
+        assert_eq!(parse_hand("[5555m)"), Err(ParseTileError::UnexpectedCharacter { position: 6, character: ')' }));
+        assert_eq!(parse_hand("[123z]"), Err(ParseTileError::InvalidMeld { position: 0 }));
+        assert_eq!(parse_tiles("[123m]"), Err(ParseTileError::UnexpectedCharacter { position: 0, character: '[' }));
+    }
+
+    #[test]
+    fn errors_name_the_bad_character() {
+        assert_eq!(parse_tiles("12x"), Err(ParseTileError::UnexpectedCharacter { position: 2, character: 'x' }));
+        assert_eq!(parse_tiles("18z"), Err(ParseTileError::InvalidTile { position: 1, character: '8', suit: 'z' }));
+        assert_eq!(parse_tiles("0z"), Err(ParseTileError::InvalidTile { position: 0, character: '0', suit: 'z' }));
+        assert_eq!(parse_tiles("123m45"), Err(ParseTileError::MissingSuit { position: 5 }));
+        assert_eq!(parse_tiles("m"), Err(ParseTileError::UnexpectedCharacter { position: 0, character: 'm' }));
+        assert_eq!(parse_hand("11z[123m"), Err(ParseTileError::UnclosedMeld { position: 3 }));
+        assert_eq!(parse_hand("[123m]11z"), Err(ParseTileError::UnexpectedCharacter { position: 6, character: '1' }));
+        assert_eq!(
+            parse_tiles("12x").unwrap_err().to_string(),
+            "unexpected character 'x' at position 2"
+        );
+    }
+}
@@ -1,23 +1,47 @@
 use std::cmp::{min, max};
-
-use strum_macros::EnumIter;
+use std::hash::{Hash, Hasher};
 
 use crate::mahjong::{tile::enums::Suit, strategy::block_strategy::{Meld, Pung, Chow, Protorun}};
 
 use super::mahjong_tile::{Meldable, Nextable, MahjongTile};
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+// `red` marks an aka five. It is deliberately left out of equality and hashing
+// so a red five still melds, pairs and counts as an ordinary five.
+#[derive(Clone, Copy, Debug)]
 pub struct SuitedTile {
     pub suit: Suit,
     pub value: i8,
+    pub red: bool,
+}
+
+impl SuitedTile {
+    pub fn new(suit: Suit, value: i8) -> Self {
+        SuitedTile { suit, value, red: false }
+    }
+
+    pub fn new_red(suit: Suit) -> Self {
+        SuitedTile { suit, value: 5, red: true }
+    }
+}
+
+impl PartialEq for SuitedTile {
+    fn eq(&self, other: &Self) -> bool {
+        self.suit == other.suit && self.value == other.value
+    }
+}
+
+impl Eq for SuitedTile {}
+
+impl Hash for SuitedTile {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.suit.hash(state);
+        self.value.hash(state);
+    }
 }
 
 impl Nextable for SuitedTile {
     fn next(&self) -> Self {
-        SuitedTile {
-            suit: self.suit,
-            value: (self.value % 9) + 1,
-        }
+        SuitedTile::new(self.suit, (self.value % 9) + 1)
     }
 }
 
@@ -25,12 +49,11 @@ impl Meldable for SuitedTile {
     fn melds(&self) -> Vec<Meld> {
         let mut melds = vec![Meld::Pung(Pung::new(MahjongTile::Suit(*self)))];
         let chiis: Vec<Meld> = (max(1, self.value - 2)..min(8, self.value + 2))
-                .into_iter()
                 .map(|v|
                   Meld::Chow(Chow::new(
-                    MahjongTile::Suit(SuitedTile{ suit: self.suit, value: v }),
-                    MahjongTile::Suit(SuitedTile{ suit: self.suit, value: v + 1 }),
-                    MahjongTile::Suit(SuitedTile{ suit: self.suit, value: v + 2 }),
+                    MahjongTile::new_suit(self.suit, v),
+                    MahjongTile::new_suit(self.suit, v + 1),
+                    MahjongTile::new_suit(self.suit, v + 2),
                   ))
                 ).collect();
         melds.extend(chiis);
@@ -39,12 +62,11 @@ impl Meldable for SuitedTile {
 
     fn protoruns(&self) -> Vec<Protorun> {
         (max(1, self.value - 2)..min(7, self.value + 2))
-                .into_iter()
                 .map(|v|
                   Protorun {
                     tiles: [
                         MahjongTile::Suit(*self),
-                        MahjongTile::Suit(SuitedTile{ suit: self.suit, value: v})
+                        MahjongTile::new_suit(self.suit, v)
                     ]
                   }
                 ).collect()
@@ -52,6 +74,7 @@ impl Meldable for SuitedTile {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -60,17 +83,25 @@ mod tests {
         let tile = SuitedTile {
             suit: Suit::Man,
             value: 8,
+            red: false,
         };
         assert_eq!(tile.next().value, 9);
         assert_eq!(tile.next().suit, Suit::Man);
         let tile = SuitedTile {
             suit: Suit::Man,
             value: 9,
+            red: false,
         };
         assert_eq!(tile.next().value, 1);
         assert_eq!(tile.next().suit, Suit::Man);
     }
 
+    #[test]
+    fn red_fives_equal_plain_fives() {
+        assert_eq!(SuitedTile::new_red(Suit::Pin), SuitedTile::new(Suit::Pin, 5));
+        assert_ne!(SuitedTile::new_red(Suit::Pin), SuitedTile::new(Suit::Sou, 5));
+    }
+
     fn gen_chow(suit: Suit, val: i8) -> Meld {
         Meld::Chow(
             Chow::new(
@@ -86,6 +117,7 @@ mod tests {
         let tile = SuitedTile {
             suit: Suit::Man,
             value: 8,
+            red: false,
         };
         assert_eq!(tile.melds().contains(&Meld::Pung(Pung::new(MahjongTile::Suit(tile)))), true);
         assert_eq!(tile.melds().contains(&gen_chow(Suit::Man, 5)), false);
@@ -95,6 +127,7 @@ mod tests {
         let tile = SuitedTile {
             suit: Suit::Man,
             value: 9,
+            red: false,
         };
         assert_eq!(tile.melds().contains(&gen_chow(Suit::Man, 6)), false);
         assert_eq!(tile.melds().contains(&gen_chow(Suit::Man, 7)), true);
@@ -0,0 +1,158 @@
+use super::enums::{Dragon, Flower, Suit, Wind};
+use super::mahjong_tile::MahjongTile;
+use super::suited_tile::SuitedTile;
+
+// Dense numbering of the 34 distinct tile kinds: 0-8 man, 9-17 pin, 18-26 sou,
+// 27-30 East/South/West/North and 31-33 White/Green/Red. Red fives share the
+// index of their plain five. Flowers have no index: they are set aside as soon
+// as they are drawn and never reach a hand, so only `TryFrom` takes a
+// `MahjongTile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TileIndex(u8);
+
+impl TileIndex {
+    pub const COUNT: usize = 34;
+
+    pub fn new(index: u8) -> Option<Self> {
+        match (index as usize) < Self::COUNT {
+            true => Some(TileIndex(index)),
+            false => None,
+        }
+    }
+
+    pub fn all() -> impl Iterator<Item = TileIndex> {
+        (0..Self::COUNT as u8).map(TileIndex)
+    }
+
+    pub fn as_usize(&self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn is_honour(&self) -> bool {
+        (27..34).contains(&self.0)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.0 < 27 && matches!(self.0 % 9, 0 | 8)
+    }
+
+    pub fn is_terminal_or_honour(&self) -> bool {
+        self.is_honour() || self.is_terminal()
+    }
+
+    pub fn suit(&self) -> Option<Suit> {
+        match self.0 / 9 {
+            0 => Some(Suit::Man),
+            1 => Some(Suit::Pin),
+            2 => Some(Suit::Sou),
+            _ => None,
+        }
+    }
+
+    // Face value 1-9 for suited tiles
+    pub fn value(&self) -> Option<i8> {
+        match self.0 < 27 {
+            true => Some((self.0 % 9) as i8 + 1),
+            false => None,
+        }
+    }
+
+    // The tile `offset` places further along the same suit, if there is one
+    pub fn offset(&self, offset: i8) -> Option<TileIndex> {
+        let value = self.value()? + offset;
+        match (1..=9).contains(&value) {
+            true => Some(TileIndex(self.0 - (self.0 % 9) + value as u8 - 1)),
+            false => None,
+        }
+    }
+
+    pub fn tile(&self) -> MahjongTile {
+        match self.0 {
+            0..=26 => MahjongTile::new_suit(self.suit().unwrap(), self.value().unwrap()),
+            27 => MahjongTile::Wind(Wind::East),
+            28 => MahjongTile::Wind(Wind::South),
+            29 => MahjongTile::Wind(Wind::West),
+            30 => MahjongTile::Wind(Wind::North),
+            31 => MahjongTile::Dragon(Dragon::White),
+            32 => MahjongTile::Dragon(Dragon::Green),
+            _ => MahjongTile::Dragon(Dragon::Red),
+        }
+    }
+}
+
+impl From<SuitedTile> for TileIndex {
+    fn from(tile: SuitedTile) -> Self {
+        let base = match tile.suit {
+            Suit::Man => 0,
+            Suit::Pin => 9,
+            Suit::Sou => 18,
+        };
+        TileIndex(base + tile.value as u8 - 1)
+    }
+}
+
+impl From<Wind> for TileIndex {
+    fn from(wind: Wind) -> Self {
+        match wind {
+            Wind::East => TileIndex(27),
+            Wind::South => TileIndex(28),
+            Wind::West => TileIndex(29),
+            Wind::North => TileIndex(30),
+        }
+    }
+}
+
+impl From<Dragon> for TileIndex {
+    fn from(dragon: Dragon) -> Self {
+        match dragon {
+            Dragon::White => TileIndex(31),
+            Dragon::Green => TileIndex(32),
+            Dragon::Red => TileIndex(33),
+        }
+    }
+}
+
+impl TryFrom<MahjongTile> for TileIndex {
+    type Error = Flower;
+
+    fn try_from(tile: MahjongTile) -> Result<Self, Flower> {
+        match tile {
+            MahjongTile::Suit(suited) => Ok(suited.into()),
+            MahjongTile::Wind(wind) => Ok(wind.into()),
+            MahjongTile::Dragon(dragon) => Ok(dragon.into()),
+            MahjongTile::Flower(flower) => Err(flower),
+        }
+    }
+}
+
+impl From<TileIndex> for MahjongTile {
+    fn from(index: TileIndex) -> Self {
+        index.tile()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_tile() {
+        for index in TileIndex::all() {
+            assert_eq!(TileIndex::try_from(index.tile()), Ok(index));
+        }
+        assert_eq!(TileIndex::all().count(), TileIndex::COUNT);
+        assert_eq!(TileIndex::new(34), None);
+    }
+
+    #[test]
+    fn classifies_tiles() {
+        let nine_pin = TileIndex::from(SuitedTile::new(Suit::Pin, 9));
+        assert_eq!(nine_pin.as_usize(), 17);
+        assert!(nine_pin.is_terminal());
+        assert_eq!(nine_pin.offset(1), None);
+        assert_eq!(nine_pin.offset(-2).map(|i| i.tile()), Some(MahjongTile::new_suit(Suit::Pin, 7)));
+        assert!(TileIndex::from(Dragon::Red).is_honour());
+        assert!(!TileIndex::from(SuitedTile::new_red(Suit::Sou)).is_terminal_or_honour());
+        assert_eq!(TileIndex::try_from(MahjongTile::Flower(Flower::Plum)), Err(Flower::Plum));
+    }
+}
@@ -0,0 +1,121 @@
+use crate::mahjong::tile::mahjong_tile::{MahjongTile, Nextable};
+
+use super::wall::Wall;
+
+const INDICATORS: usize = 5;
+
+// The fourteen tiles set aside at the break: four replacement (rinshan)
+// tiles for kans, and five dora indicators with an ura-dora indicator under
+// each. One indicator is flipped at the start and another after every kan.
+pub struct DeadWall {
+  rinshan: Vec<MahjongTile>,
+  indicators: Vec<MahjongTile>,
+  ura_indicators: Vec<MahjongTile>,
+  revealed: usize,
+}
+
+impl DeadWall {
+  pub const SIZE: usize = 14;
+
+  pub fn new(tiles: Vec<MahjongTile>) -> Self {
+    assert_eq!(tiles.len(), Self::SIZE, "the dead wall holds {} tiles", Self::SIZE);
+    DeadWall {
+      rinshan: tiles[0..4].to_vec(),
+      indicators: tiles[4..9].to_vec(),
+      ura_indicators: tiles[9..14].to_vec(),
+      revealed: 1,
+    }
+  }
+
+  pub fn dora_indicators(&self) -> &[MahjongTile] {
+    &self.indicators[..self.revealed]
+  }
+
+  // Only looked at when a riichi hand wins
+  pub fn ura_dora_indicators(&self) -> &[MahjongTile] {
+    &self.ura_indicators[..self.revealed]
+  }
+
+  pub fn dora(&self) -> Vec<MahjongTile> {
+    self.dora_indicators().iter().map(|t| t.next()).collect()
+  }
+
+  pub fn ura_dora(&self) -> Vec<MahjongTile> {
+    self.ura_dora_indicators().iter().map(|t| t.next()).collect()
+  }
+
+  // Takes the replacement tile after a kan. The last tile of the live wall
+  // moves across so the dead wall stays at fourteen tiles.
+  pub fn draw_rinshan(&mut self, wall: &mut Wall) -> Option<MahjongTile> {
+    let tile = self.rinshan.pop()?;
+    if let Some(replacement) = wall.draw_from_tail() {
+      self.rinshan.insert(0, replacement);
+    }
+    Some(tile)
+  }
+
+  // Flips the next kan dora indicator, returning it
+  pub fn reveal_kan_dora(&mut self) -> Option<MahjongTile> {
+    if self.revealed == INDICATORS {
+      return None;
+    }
+    self.revealed += 1;
+    self.indicators.get(self.revealed - 1).copied()
+  }
+
+  pub fn tiles(&self) -> Vec<MahjongTile> {
+    self.rinshan.iter()
+      .chain(self.indicators.iter())
+      .chain(self.ura_indicators.iter())
+      .copied()
+      .collect()
+  }
+
+  pub fn len(&self) -> usize {
+    self.rinshan.len() + self.indicators.len() + self.ura_indicators.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::mahjong::tile::notation::parse_tiles;
+
+  use super::*;
+
+  fn dead_wall() -> DeadWall {
+    // Rinshan 1234m, indicators 9m 5z 7z 4z 9s, ura 1p 2p 3p 4p 5p
+    DeadWall::new(parse_tiles("1234m9m5z7z4z9s12345p").unwrap())
+  }
+
+  #[test]
+  fn indicators_point_at_the_next_tile() {
+    let mut dead_wall = dead_wall();
+    assert_eq!(dead_wall.dora_indicators(), parse_tiles("9m").unwrap());
+    assert_eq!(dead_wall.dora(), parse_tiles("1m").unwrap());
+    assert_eq!(dead_wall.ura_dora(), parse_tiles("2p").unwrap());
+    dead_wall.reveal_kan_dora();
+    dead_wall.reveal_kan_dora();
+    dead_wall.reveal_kan_dora();
+    // White -> Green, Red -> White, North -> East
+    assert_eq!(dead_wall.dora(), parse_tiles("1m651z").unwrap());
+    assert_eq!(dead_wall.reveal_kan_dora(), Some("9s".parse().unwrap()));
+    assert_eq!(dead_wall.reveal_kan_dora(), None);
+    assert_eq!(dead_wall.dora_indicators().len(), 5);
+  }
+
+  #[test]
+  fn rinshan_draws_are_replenished_from_the_live_wall() {
+    let mut dead_wall = dead_wall();
+    let mut wall = Wall::from_tiles(parse_tiles("11122233344455566677z").unwrap());
+    wall.split_dead_wall();
+    let live = wall.remaining();
+    assert_eq!(dead_wall.draw_rinshan(&mut wall), Some("4m".parse().unwrap()));
+    assert_eq!(dead_wall.draw_rinshan(&mut wall), Some("3m".parse().unwrap()));
+    assert_eq!(dead_wall.len(), DeadWall::SIZE);
+    assert_eq!(wall.remaining(), live - 2);
+  }
+}
@@ -1 +1,4 @@
-pub mod wall;
\ No newline at end of file
+pub mod dead_wall;
+pub mod tile_set;
+#[allow(clippy::module_inception)]
+pub mod wall;
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use strum::IntoEnumIterator;
+
+use crate::mahjong::tile::{mahjong_tile::{MahjongTile, Nextable}, enums::{Wind, Dragon, Suit, Flower}};
+
+// Which physical tiles make up the wall: four of every tile kind, with some
+// fives swapped for red fives, optional flowers and seasons, and 2-8 man
+// removed for three player games
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileSet {
+  // Red fives per suit, in man, pin, sou order
+  pub red_fives: [u8; 3],
+  pub flowers: bool,
+  pub sanma: bool,
+}
+
+impl TileSet {
+  // The standard 136 tile set without red fives
+  pub fn standard() -> Self {
+    TileSet { red_fives: [0, 0, 0], flowers: false, sanma: false }
+  }
+
+  pub fn with_red_fives(self, man: u8, pin: u8, sou: u8) -> Self {
+    TileSet { red_fives: [man, pin, sou], ..self }
+  }
+
+  pub fn with_flowers(self) -> Self {
+    TileSet { flowers: true, ..self }
+  }
+
+  pub fn sanma(self) -> Self {
+    TileSet { sanma: true, ..self }
+  }
+
+  pub fn red_fives_in(&self, suit: Suit) -> u8 {
+    match suit {
+      Suit::Man => self.red_fives[0],
+      Suit::Pin => self.red_fives[1],
+      Suit::Sou => self.red_fives[2],
+    }
+  }
+
+  pub fn tiles(&self) -> Vec<MahjongTile> {
+    let mut tiles = Vec::new();
+    for _ in 0..4 {
+      Wind::iter().for_each(|wind| tiles.push(MahjongTile::Wind(wind)));
+      Dragon::iter().for_each(|dragon| tiles.push(MahjongTile::Dragon(dragon)));
+    }
+    for suit in Suit::iter() {
+      for value in 1..=9 {
+        let tile = MahjongTile::new_suit(suit, value);
+        for _ in 0..self.expected_count(tile) {
+          tiles.push(tile);
+        }
+      }
+      for tile in tiles.iter_mut().filter(|t| **t == MahjongTile::new_suit(suit, 5)).take(self.red_fives_in(suit) as usize) {
+        *tile = MahjongTile::new_red(suit);
+      }
+    }
+    if self.flowers {
+      Flower::iter().for_each(|flower| tiles.push(MahjongTile::Flower(flower)));
+    }
+    tiles
+  }
+
+  pub fn len(&self) -> usize {
+    self.tiles().len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  // Copies of a tile kind in the set, counting red fives as fives
+  pub fn expected_count(&self, tile: MahjongTile) -> usize {
+    match tile {
+      MahjongTile::Flower(_) => self.flowers as usize,
+      MahjongTile::Suit(suited) if self.sanma && suited.suit == Suit::Man && (2..=8).contains(&suited.value) => 0,
+      _ => 4,
+    }
+  }
+
+  // The dora shown by `indicator`: the next kind along that the set has, so
+  // 1m points at 9m in sanma
+  pub fn dora(&self, indicator: MahjongTile) -> MahjongTile {
+    let mut dora = indicator.next();
+    while self.expected_count(dora) == 0 && !dora.is_flower() {
+      dora = dora.next();
+    }
+    dora
+  }
+
+  // Checks a complete accounting of every tile in play, wherever it is,
+  // against the set. Red fives must match exactly as well.
+  pub fn verify(&self, tiles: &[MahjongTile]) -> Result<(), String> {
+    let mut expected = HashMap::new();
+    for tile in self.tiles() {
+      *expected.entry((tile, tile.is_red())).or_insert(0) += 1;
+    }
+    let mut found = HashMap::new();
+    for tile in tiles {
+      *found.entry((*tile, tile.is_red())).or_insert(0) += 1;
+    }
+    for key in expected.keys().chain(found.keys()) {
+      let (wanted, got) = (expected.get(key).unwrap_or(&0), found.get(key).unwrap_or(&0));
+      if wanted != got {
+        let (tile, red) = key;
+        return Err(format!("expected {} of {}{} but found {}", wanted, tile, if *red { " (red)" } else { "" }, got));
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Default for TileSet {
+  fn default() -> Self {
+    Self::standard()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn standard_set_has_136_tiles() {
+    let set = TileSet::standard();
+    assert_eq!(set.len(), 136);
+    assert_eq!(set.tiles().iter().filter(|t| **t == MahjongTile::new_suit(Suit::Sou, 9)).count(), 4);
+    assert_eq!(set.verify(&set.tiles()), Ok(()));
+  }
+
+  #[test]
+  fn variants() {
+    let red = TileSet::standard().with_red_fives(1, 2, 1);
+    assert_eq!(red.len(), 136);
+    assert_eq!(red.tiles().iter().filter(|t| t.is_red()).count(), 4);
+    assert_eq!(TileSet::standard().with_flowers().len(), 144);
+    assert_eq!(TileSet::standard().sanma().len(), 108);
+  }
+
+  #[test]
+  fn dora_skips_missing_kinds() {
+    let one_man = MahjongTile::new_suit(Suit::Man, 1);
+    assert_eq!(TileSet::standard().dora(one_man), MahjongTile::new_suit(Suit::Man, 2));
+    assert_eq!(TileSet::standard().sanma().dora(one_man), MahjongTile::new_suit(Suit::Man, 9));
+    assert_eq!(TileSet::standard().sanma().dora(MahjongTile::Wind(Wind::West)), MahjongTile::Wind(Wind::North));
+  }
+
+  #[test]
+  fn verify_spots_missing_and_extra_tiles() {
+    let set = TileSet::standard().with_red_fives(1, 1, 1);
+    let mut tiles = set.tiles();
+    let five = tiles.iter().position(|t| *t == MahjongTile::new_suit(Suit::Man, 5) && !t.is_red()).unwrap();
+    tiles[five] = MahjongTile::new_red(Suit::Man);
+    assert!(set.verify(&tiles).is_err());
+    tiles.pop();
+    assert!(TileSet::standard().verify(&tiles).is_err());
+  }
+}
@@ -1,42 +1,116 @@
-use rand::{seq::SliceRandom, thread_rng};
-use strum::IntoEnumIterator;
+use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng};
 
-use crate::mahjong::tile::{mahjong_tile::MahjongTile, enums::{Wind, Dragon, Suit}};
+use crate::mahjong::tile::mahjong_tile::MahjongTile;
 
+use super::{dead_wall::DeadWall, tile_set::TileSet};
+
+// Tiles are drawn from the end of `tiles`, with the dead wall split off the
+// very end, so the first tile drawn sits next to the break and index 0 is the
+// last tile of the hand
 pub struct Wall {
-  tiles: Vec<MahjongTile>
+  tiles: Vec<MahjongTile>,
+  seed: Option<u64>,
 }
 
 impl Wall {
   pub fn new() -> Self {
-    let mut tiles = Vec::new();
-    for _ in 0..4 {
-        Wind::iter().for_each(|wind| tiles.push(MahjongTile::Wind(wind)));
-        Dragon::iter().for_each(|dragon| tiles.push(MahjongTile::Dragon(dragon)));
-        Suit::iter().for_each(|suit| {
-            for value in 1..9 {
-                tiles.push(MahjongTile::new_suit(suit, value))
-            }
-        });
+    Self::from_tile_set(&TileSet::standard())
+  }
+
+  pub fn from_tile_set(tile_set: &TileSet) -> Self {
+    Self::from_tile_set_with_seed(tile_set, thread_rng().gen())
+  }
+
+  // The same seed always gives the same wall
+  pub fn with_seed(seed: u64) -> Self {
+    Self::from_tile_set_with_seed(&TileSet::standard(), seed)
+  }
+
+  pub fn from_tile_set_with_seed(tile_set: &TileSet, seed: u64) -> Self {
+    let mut tiles = tile_set.tiles();
+    tiles.shuffle(&mut StdRng::seed_from_u64(seed));
+    Wall{
+      tiles,
+      seed: Some(seed),
     }
-    tiles.shuffle(&mut thread_rng());
+  }
+
+  // A wall in a fixed order for scripted games: tiles are listed in the order
+  // they will be dealt and drawn, and the final 14 become the dead wall
+  pub fn from_tiles(tiles: Vec<MahjongTile>) -> Self {
+    let split = tiles.len().saturating_sub(14);
+    let (live, dead) = tiles.split_at(split);
+    let mut ordered: Vec<MahjongTile> = live.iter().rev().copied().collect();
+    ordered.extend_from_slice(dead);
     Wall{
-      tiles
+      tiles: ordered,
+      seed: None,
     }
   }
 
-  pub fn split_dead_wall(&mut self) -> Vec<MahjongTile> {
-    let tiles = self.tiles.clone();
-    let (wall, deadwall) = tiles.split_at(tiles.len() - 15);
-    self.tiles = wall.to_vec();
-    deadwall.to_vec()
+  // The seed the wall was shuffled with, if it was shuffled at all
+  pub fn seed(&self) -> Option<u64> {
+    self.seed
+  }
+
+  pub fn split_dead_wall(&mut self) -> DeadWall {
+    let dead_wall = self.tiles.split_off(self.tiles.len() - DeadWall::SIZE);
+    DeadWall::new(dead_wall)
+  }
+
+  pub fn tiles(&self) -> &[MahjongTile] {
+    &self.tiles
+  }
+
+  pub fn remaining(&self) -> usize {
+    self.tiles.len()
   }
 
   pub fn has_tiles(&self) -> bool {
-    self.tiles.len() > 0
+    !self.tiles.is_empty()
   }
 
   pub fn draw(&mut self) -> Option<MahjongTile> {
     self.tiles.pop()
   }
-}
\ No newline at end of file
+
+  // Takes the tile that would otherwise be drawn last
+  pub fn draw_from_tail(&mut self) -> Option<MahjongTile> {
+    match self.tiles.is_empty() {
+      true => None,
+      false => Some(self.tiles.remove(0)),
+    }
+  }
+}
+
+impl Default for Wall {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::mahjong::tile::notation::parse_tiles;
+
+  use super::*;
+
+  #[test]
+  fn seeded_walls_repeat() {
+    assert_eq!(Wall::with_seed(42).tiles(), Wall::with_seed(42).tiles());
+    assert_ne!(Wall::with_seed(42).tiles(), Wall::with_seed(43).tiles());
+    assert_eq!(Wall::with_seed(42).seed(), Some(42));
+  }
+
+  #[test]
+  fn scripted_walls_draw_in_order() {
+    let mut tiles = parse_tiles("123m").unwrap();
+    tiles.extend(parse_tiles("11112222333344z").unwrap());
+    let mut wall = Wall::from_tiles(tiles);
+    assert_eq!(wall.split_dead_wall().tiles(), parse_tiles("11112222333344z").unwrap());
+    assert_eq!(wall.draw(), Some("1m".parse().unwrap()));
+    assert_eq!(wall.draw(), Some("2m".parse().unwrap()));
+    assert_eq!(wall.draw(), Some("3m".parse().unwrap()));
+    assert_eq!(wall.draw(), None);
+  }
+}
@@ -0,0 +1,45 @@
+use crate::mahjong::tile::enums::Wind;
+
+// What the winning hand can't tell by itself: where the player sat, how the
+// winning tile was got and what had been declared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WinContext {
+    pub seat_wind: Wind,
+    pub round_wind: Wind,
+    pub tsumo: bool,
+    pub riichi: bool,
+    pub double_riichi: bool,
+    pub ippatsu: bool,
+    // Won on the last tile: haitei when drawn, houtei when discarded
+    pub last_tile: bool,
+    // Won on the replacement tile after a kan
+    pub rinshan: bool,
+    // Won by robbing an added kan
+    pub chankan: bool,
+    // Won on the player's first draw, with no calls made before it
+    pub first_draw: bool,
+    // The winning tile was the last of its kind not yet on show (MCR only)
+    pub last_of_kind: bool,
+}
+
+impl WinContext {
+    pub fn new(seat_wind: Wind, round_wind: Wind) -> Self {
+        WinContext {
+            seat_wind,
+            round_wind,
+            tsumo: false,
+            riichi: false,
+            double_riichi: false,
+            ippatsu: false,
+            last_tile: false,
+            rinshan: false,
+            chankan: false,
+            first_draw: false,
+            last_of_kind: false,
+        }
+    }
+
+    pub fn is_dealer(&self) -> bool {
+        self.seat_wind == Wind::East
+    }
+}
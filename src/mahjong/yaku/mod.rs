@@ -0,0 +1,4 @@
+pub mod context;
+pub mod score;
+#[allow(clippy::module_inception)]
+pub mod yaku;
@@ -0,0 +1,345 @@
+use std::fmt;
+
+use crate::mahjong::analysis::agari::Decomposition;
+use crate::mahjong::analysis::machi::{Wait, WaitKind};
+use crate::mahjong::ruleset::ruleset::Ruleset;
+use crate::mahjong::strategy::block_strategy::Meld;
+use crate::mahjong::tile::{hand_counts::HandCounts, mahjong_tile::MahjongTile};
+
+use super::context::WinContext;
+use super::yaku::{find_yaku, is_yakuhai, readings, total_han, SetKind, Shape, Yaku};
+
+// Hands worth enough han are paid a fixed amount whatever their fu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Mangan,
+    Haneman,
+    Baiman,
+    Sanbaiman,
+    // Thirteen han or more without a yakuman
+    KazoeYakuman,
+    // Counting double yakuman and stacked yakuman
+    Yakuman(u8),
+}
+
+impl Limit {
+    pub fn base_points(&self) -> u32 {
+        match self {
+            Limit::Mangan => 2000,
+            Limit::Haneman => 3000,
+            Limit::Baiman => 4000,
+            Limit::Sanbaiman => 6000,
+            Limit::KazoeYakuman => 8000,
+            Limit::Yakuman(count) => 8000 * *count as u32,
+        }
+    }
+}
+
+// Han from dora tiles in the hand. They only count on top of a yaku.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dora {
+    pub dora: u32,
+    // Under the indicators, turned over for riichi wins only
+    pub ura_dora: u32,
+    // Red fives
+    pub aka_dora: u32,
+    // Norths set aside in sanma
+    pub nukidora: u32,
+}
+
+impl Dora {
+    // `tiles` is every tile of the winning hand, called ones included. Each
+    // indicator counts separately, so a tile can be dora more than once.
+    pub fn count(tiles: &[MahjongTile], dora: &[MahjongTile], ura_dora: &[MahjongTile]) -> Self {
+        let matches = |dora: &[MahjongTile]| tiles.iter()
+            .map(|tile| dora.iter().filter(|d| *d == tile).count() as u32)
+            .sum();
+        Dora {
+            dora: matches(dora),
+            ura_dora: matches(ura_dora),
+            aka_dora: tiles.iter().filter(|t| t.is_red()).count() as u32,
+            nukidora: 0,
+        }
+    }
+
+    pub fn han(&self) -> u32 {
+        self.dora + self.ura_dora + self.aka_dora + self.nukidora
+    }
+}
+
+// The value of a winning hand and what it costs the other players
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub yaku: Vec<Yaku>,
+    pub dora: Dora,
+    // Dora included
+    pub han: u32,
+    pub fu: u32,
+    pub limit: Option<Limit>,
+    pub dealer: bool,
+    pub tsumo: bool,
+    // 4 han 30 fu and 3 han 60 fu are rounded up to mangan
+    pub kiriage: bool,
+}
+
+impl Score {
+    pub fn new(yaku: Vec<Yaku>, fu: u32, closed: bool, context: &WinContext) -> Self {
+        let han = total_han(&yaku, closed);
+        let limit = limit(&yaku, han, fu, false);
+        Score { yaku, dora: Dora::default(), han, fu, limit, dealer: context.is_dealer(), tsumo: context.tsumo, kiriage: false }
+    }
+
+    pub fn with_kiriage(self) -> Self {
+        let limit = limit(&self.yaku, self.han, self.fu, true);
+        Score { limit, kiriage: true, ..self }
+    }
+
+    // Adds the han from dora. A yakuman is worth the same with or without.
+    pub fn with_dora(self, dora: Dora) -> Self {
+        let han = self.han - self.dora.han() + dora.han();
+        let limit = limit(&self.yaku, han, self.fu, self.kiriage);
+        Score { dora, han, limit, ..self }
+    }
+
+    // Everything is paid as a multiple of this
+    pub fn base_points(&self) -> u32 {
+        match self.limit {
+            Some(limit) => limit.base_points(),
+            None => self.fu << (self.han + 2),
+        }
+    }
+
+    // What the discarder pays for a ron
+    pub fn ron(&self) -> u32 {
+        round_up(self.base_points() * if self.dealer { 6 } else { 4 })
+    }
+
+    // What one other player pays for a tsumo. The dealer pays double, and
+    // pays like everyone else when they are the one winning.
+    pub fn tsumo_from(&self, dealer: bool) -> u32 {
+        round_up(self.base_points() * if self.dealer || dealer { 2 } else { 1 })
+    }
+
+    // Points the winner collects from the table
+    pub fn total(&self) -> u32 {
+        match (self.tsumo, self.dealer) {
+            (false, _) => self.ron(),
+            (true, true) => 3 * self.tsumo_from(false),
+            (true, false) => self.tsumo_from(true) + 2 * self.tsumo_from(false),
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut yaku: Vec<String> = self.yaku.iter().map(|y| format!("{:?}", y)).collect();
+        for (name, count) in [("Dora", self.dora.dora), ("UraDora", self.dora.ura_dora), ("AkaDora", self.dora.aka_dora), ("Nukidora", self.dora.nukidora)] {
+            if count > 0 {
+                yaku.push(format!("{} {}", name, count));
+            }
+        }
+        match self.limit {
+            Some(Limit::Yakuman(_)) => write!(f, "{}", yaku.join(", "))?,
+            _ => write!(f, "{} ({} han {} fu)", yaku.join(", "), self.han, self.fu)?,
+        }
+        match self.limit {
+            Some(limit) => write!(f, ", {:?}", limit),
+            None => Ok(()),
+        }
+    }
+}
+
+fn limit(yaku: &[Yaku], han: u32, fu: u32, kiriage: bool) -> Option<Limit> {
+    let yakuman: u8 = yaku.iter().map(|y| y.yakuman()).sum();
+    match han {
+        _ if yakuman > 0 => Some(Limit::Yakuman(yakuman)),
+        13.. => Some(Limit::KazoeYakuman),
+        11..=12 => Some(Limit::Sanbaiman),
+        8..=10 => Some(Limit::Baiman),
+        6..=7 => Some(Limit::Haneman),
+        5 => Some(Limit::Mangan),
+        // 4 han 40 fu and 3 han 70 fu are already worth more than mangan
+        _ if fu << (han + 2) > 2000 => Some(Limit::Mangan),
+        4 if kiriage && fu == 30 => Some(Limit::Mangan),
+        3 if kiriage && fu == 60 => Some(Limit::Mangan),
+        _ => None,
+    }
+}
+
+fn round_up(points: u32) -> u32 {
+    points.div_ceil(100) * 100
+}
+
+// Fu for one reading of a winning hand with the given yaku, rounded up to
+// the next ten. Seven pairs are always 25.
+pub fn fu(wait: &Wait, yaku: &[Yaku], context: &WinContext) -> u32 {
+    match wait.decomposition {
+        Decomposition::SevenPairs(_) => return 25,
+        // Only ever a yakuman
+        Decomposition::ThirteenOrphans { .. } => return 30,
+        Decomposition::Standard { .. } => (),
+    }
+    let closed = wait.decomposition.is_closed();
+    if context.tsumo && yaku.contains(&Yaku::Pinfu) {
+        return 20;
+    }
+
+    let shape = Shape::new(wait, context);
+    let mut fu = 20;
+    if closed && !context.tsumo {
+        fu += 10;
+    }
+    if context.tsumo {
+        fu += 2;
+    }
+    for set in shape.sets.iter().filter(|s| s.is_triplet()) {
+        let mut set_fu = 2;
+        if set.concealed {
+            set_fu *= 2;
+        }
+        if set.tile.is_terminal_or_honour() {
+            set_fu *= 2;
+        }
+        if set.kind == SetKind::Kong {
+            set_fu *= 4;
+        }
+        fu += set_fu;
+    }
+    // A pair of the seat wind in its own round counts twice
+    if let Some(pair) = shape.pair {
+        match pair.tile() {
+            MahjongTile::Wind(wind) => {
+                fu += 2 * ((wind == context.seat_wind) as u32 + (wind == context.round_wind) as u32);
+            }
+            _ if is_yakuhai(pair, context) => fu += 2,
+            _ => (),
+        }
+    }
+    if matches!(wait.kind, WaitKind::Kanchan | WaitKind::Penchan | WaitKind::Tanki | WaitKind::Nobetan) {
+        fu += 2;
+    }
+    // An open hand with nothing else to its name still gets 30
+    if !closed && fu == 20 {
+        fu = 30;
+    }
+    fu.div_ceil(10) * 10
+}
+
+// The best paying reading of a hand won on `tile`, with `concealed` already
+// holding it. None when no reading has a yaku.
+pub fn best_score(concealed: &HandCounts, called: &[Meld], tile: MahjongTile, context: &WinContext, ruleset: &Ruleset) -> Option<Score> {
+    readings(concealed, called, tile).iter()
+        .map(|wait| {
+            let closed = wait.decomposition.is_closed();
+            let mut yaku = find_yaku(wait, context);
+            if !closed && !ruleset.kuitan {
+                yaku.retain(|y| *y != Yaku::Tanyao);
+            }
+            let fu = fu(wait, &yaku, context);
+            let score = Score::new(yaku, fu, closed, context);
+            match ruleset.kiriage_mangan {
+                true => score.with_kiriage(),
+                false => score,
+            }
+        })
+        // No dora yet, so this is the han from yaku alone
+        .filter(|score| !score.yaku.is_empty() && score.han >= ruleset.minimum_han)
+        .max_by_key(|score| (score.total(), score.han))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mahjong::tile::{enums::Wind, notation::{parse_hand, parse_tiles}};
+
+    use super::*;
+
+    // `hand` holds all fourteen tiles, the winning one included
+    fn score(hand: &str, tile: &str, context: WinContext) -> Score {
+        scored(hand, tile, context, &Ruleset::default()).unwrap()
+    }
+
+    fn scored(hand: &str, tile: &str, context: WinContext, ruleset: &Ruleset) -> Option<Score> {
+        let hand = parse_hand(hand).unwrap();
+        best_score(&HandCounts::from_tiles(&hand.tiles), &hand.melds, tile.parse().unwrap(), &context, ruleset)
+    }
+
+    fn ron() -> WinContext {
+        WinContext::new(Wind::South, Wind::East)
+    }
+
+    fn tsumo() -> WinContext {
+        WinContext { tsumo: true, ..ron() }
+    }
+
+    #[test]
+    fn pinfu_fu() {
+        let pinfu = score("234m456p678s22p567m", "7m", tsumo());
+        assert_eq!((pinfu.han, pinfu.fu), (3, 20));
+        assert_eq!((pinfu.tsumo_from(true), pinfu.tsumo_from(false)), (1300, 700));
+        assert_eq!(pinfu.total(), 2700);
+        let pinfu = score("234m456p678s22p567m", "7m", ron());
+        assert_eq!((pinfu.han, pinfu.fu, pinfu.ron()), (2, 30, 2000));
+    }
+
+    #[test]
+    fn fu_from_sets_pairs_and_waits() {
+        // 20 base, 10 closed ron, 8 each for 222z and 555z, 2 for the
+        // kanchan: 48 rounds to 50
+        let hand = score("222z555z24m789p99s3m", "3m", WinContext::new(Wind::South, Wind::South));
+        assert_eq!(hand.yaku, vec![Yaku::SeatWind, Yaku::RoundWind, Yaku::Haku]);
+        assert_eq!((hand.han, hand.fu), (3, 50));
+        assert_eq!(hand.ron(), 6400);
+        // An open hand that would be 20 fu gets 30
+        let open = score("234m456p88s[678s][555p]", "8s", ron());
+        assert_eq!((open.han, open.fu, open.ron()), (1, 30, 1000));
+        // A closed kan of honours is 32 on its own
+        let kan = score("234m567p88s123s(7777z)", "8s", ron());
+        assert_eq!(kan.fu, 70);
+        assert_eq!(score("1122m3355p6677s11z", "1z", ron()).fu, 25);
+    }
+
+    #[test]
+    fn dora_add_han_but_not_yaku() {
+        let tiles = parse_tiles("234m406p678s22p567m").unwrap();
+        // 2m is dora twice over, and the 22p pair is ura-dora
+        let dora = Dora::count(&tiles, &parse_tiles("2m2m").unwrap(), &parse_tiles("2p").unwrap());
+        assert_eq!(dora, Dora { dora: 2, ura_dora: 2, aka_dora: 1, nukidora: 0 });
+        let pinfu = score("234m456p678s22p567m", "7m", ron()).with_dora(dora);
+        assert_eq!((pinfu.han, pinfu.limit), (7, Some(Limit::Haneman)));
+        assert_eq!(pinfu.with_dora(Dora::default()).han, 2);
+        let yakuman = score("111m222p333s44z555z", "5z", tsumo()).with_dora(dora);
+        assert_eq!(yakuman.limit, Some(Limit::Yakuman(1)));
+    }
+
+    #[test]
+    fn limits() {
+        let dealer = WinContext::new(Wind::East, Wind::East);
+        let dealer_hand = score("111z555z24m789p99s3m", "3m", dealer);
+        assert_eq!(dealer_hand.limit, None);
+        assert_eq!(dealer_hand.ron(), 9600);
+        let chinitsu = score("123456789m11m789m", "1m", ron());
+        assert_eq!(chinitsu.han, 10);
+        assert_eq!(chinitsu.limit, Some(Limit::Baiman));
+        assert_eq!(chinitsu.ron(), 16000);
+        let yakuman = score("111m222p333s44z555z", "4z", WinContext { tsumo: true, ..dealer });
+        assert_eq!(yakuman.limit, Some(Limit::Yakuman(2)));
+        assert_eq!(yakuman.total(), 96000);
+        let kazoe = Score::new(vec![Yaku::Riichi, Yaku::Chinitsu, Yaku::Ryanpeikou, Yaku::Pinfu, Yaku::Ittsu], 30, true, &ron());
+        assert_eq!(kazoe.limit, Some(Limit::KazoeYakuman));
+        assert_eq!(kazoe.ron(), 32000);
+    }
+
+    #[test]
+    fn ruleset_options() {
+        let open_tanyao = "234m456p88s[678s][555p]";
+        assert!(scored(open_tanyao, "8s", ron(), &Ruleset::default()).is_some());
+        assert_eq!(scored(open_tanyao, "8s", ron(), &Ruleset { kuitan: false, ..Ruleset::default() }), None);
+        let riichi = WinContext { riichi: true, ..ron() };
+        let one_han = "234m456p678s22z567m";
+        assert!(scored(one_han, "7m", riichi, &Ruleset::default()).is_some());
+        assert_eq!(scored(one_han, "7m", riichi, &Ruleset { minimum_han: 2, ..Ruleset::default() }), None);
+        let four_thirty = Score::new(vec![Yaku::Riichi, Yaku::MenzenTsumo, Yaku::Tanyao, Yaku::Iipeikou], 30, true, &ron());
+        assert_eq!(four_thirty.ron(), 7700);
+        assert_eq!(four_thirty.with_kiriage().ron(), 8000);
+    }
+}
@@ -0,0 +1,557 @@
+use crate::mahjong::analysis::agari::{decompositions, Decomposition};
+use crate::mahjong::analysis::machi::{classify, Wait, WaitKind};
+use crate::mahjong::strategy::block_strategy::Meld;
+use crate::mahjong::tile::{enums::{Dragon, Suit}, hand_counts::HandCounts, mahjong_tile::MahjongTile, tile_index::TileIndex};
+
+use super::context::WinContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Yaku {
+    // One han
+    Riichi,
+    Ippatsu,
+    MenzenTsumo,
+    Pinfu,
+    Iipeikou,
+    Haitei,
+    Houtei,
+    Rinshan,
+    Chankan,
+    Tanyao,
+    SeatWind,
+    RoundWind,
+    Haku,
+    Hatsu,
+    Chun,
+    // Two han
+    DoubleRiichi,
+    Chanta,
+    SanshokuDoujun,
+    Ittsu,
+    Toitoi,
+    Sanankou,
+    SanshokuDoukou,
+    Sankantsu,
+    Chiitoitsu,
+    Honroutou,
+    Shousangen,
+    // Three han
+    Honitsu,
+    Junchan,
+    Ryanpeikou,
+    // Six han
+    Chinitsu,
+    // Yakuman
+    KokushiMusou,
+    KokushiJuusanmen,
+    Suuankou,
+    SuuankouTanki,
+    Daisangen,
+    Shousuushii,
+    Daisuushii,
+    Tsuuiisou,
+    Chinroutou,
+    Ryuuiisou,
+    ChuurenPoutou,
+    JunseiChuuren,
+    Suukantsu,
+    Tenhou,
+    Chiihou,
+}
+
+impl Yaku {
+    // Han for the yaku, a yakuman counting 13 a time. Some are worth one han
+    // less with an open hand (kuisagari).
+    pub fn han(&self, closed: bool) -> u32 {
+        let open = !closed as u32;
+        match self {
+            Yaku::Riichi | Yaku::Ippatsu | Yaku::MenzenTsumo | Yaku::Pinfu | Yaku::Iipeikou
+                | Yaku::Haitei | Yaku::Houtei | Yaku::Rinshan | Yaku::Chankan | Yaku::Tanyao
+                | Yaku::SeatWind | Yaku::RoundWind | Yaku::Haku | Yaku::Hatsu | Yaku::Chun => 1,
+            Yaku::DoubleRiichi | Yaku::Toitoi | Yaku::Sanankou | Yaku::SanshokuDoukou | Yaku::Sankantsu
+                | Yaku::Chiitoitsu | Yaku::Honroutou | Yaku::Shousangen => 2,
+            Yaku::Chanta | Yaku::SanshokuDoujun | Yaku::Ittsu => 2 - open,
+            Yaku::Honitsu | Yaku::Junchan => 3 - open,
+            Yaku::Ryanpeikou => 3,
+            Yaku::Chinitsu => 6 - open,
+            _ => 13 * self.yakuman() as u32,
+        }
+    }
+
+    // How many yakuman the yaku is worth, 0 for the ordinary ones
+    pub fn yakuman(&self) -> u8 {
+        match self {
+            Yaku::KokushiJuusanmen | Yaku::SuuankouTanki | Yaku::Daisuushii | Yaku::JunseiChuuren => 2,
+            Yaku::KokushiMusou | Yaku::Suuankou | Yaku::Daisangen | Yaku::Shousuushii | Yaku::Tsuuiisou
+                | Yaku::Chinroutou | Yaku::Ryuuiisou | Yaku::ChuurenPoutou | Yaku::Suukantsu
+                | Yaku::Tenhou | Yaku::Chiihou => 1,
+            _ => 0,
+        }
+    }
+}
+
+pub fn total_han(yaku: &[Yaku], closed: bool) -> u32 {
+    yaku.iter().map(|y| y.han(closed)).sum()
+}
+
+// Every reading of a hand won on `tile`, with `concealed` already holding it
+pub fn readings(concealed: &HandCounts, called: &[Meld], tile: MahjongTile) -> Vec<Wait> {
+    decompositions(concealed, called).into_iter()
+        .flat_map(|decomposition| classify(&decomposition, tile).into_iter()
+            .map(move |kind| Wait { tile, kind, decomposition: decomposition.clone() }))
+        .collect()
+}
+
+// The yaku of whichever reading is worth the most han, empty if none has any
+pub fn best_yaku(concealed: &HandCounts, called: &[Meld], tile: MahjongTile, context: &WinContext) -> Vec<Yaku> {
+    readings(concealed, called, tile).iter()
+        .map(|wait| (find_yaku(wait, context), wait.decomposition.is_closed()))
+        .max_by_key(|(yaku, closed)| total_han(yaku, *closed))
+        .map(|(yaku, _)| yaku)
+        .unwrap_or_default()
+}
+
+// The yaku of one reading of a winning hand. Yakuman replace everything else.
+pub fn find_yaku(wait: &Wait, context: &WinContext) -> Vec<Yaku> {
+    let shape = Shape::new(wait, context);
+    let yakuman = yakuman(&shape, wait, context);
+    if !yakuman.is_empty() {
+        return yakuman;
+    }
+
+    let mut found = Vec::new();
+    let closed = wait.decomposition.is_closed();
+    if context.double_riichi {
+        found.push(Yaku::DoubleRiichi);
+    } else if context.riichi {
+        found.push(Yaku::Riichi);
+    }
+    if context.ippatsu && (context.riichi || context.double_riichi) {
+        found.push(Yaku::Ippatsu);
+    }
+    if context.tsumo && closed {
+        found.push(Yaku::MenzenTsumo);
+    }
+    if context.last_tile {
+        match (context.tsumo, context.rinshan) {
+            (true, false) => found.push(Yaku::Haitei),
+            (false, _) => found.push(Yaku::Houtei),
+            _ => (),
+        }
+    }
+    if context.rinshan && context.tsumo {
+        found.push(Yaku::Rinshan);
+    }
+    if context.chankan {
+        found.push(Yaku::Chankan);
+    }
+    if shape.tiles.iter().all(|t| !t.is_terminal_or_honour()) {
+        found.push(Yaku::Tanyao);
+    }
+    if let Decomposition::SevenPairs(_) = wait.decomposition {
+        found.push(Yaku::Chiitoitsu);
+    }
+
+    for set in shape.sets.iter().filter(|s| s.is_triplet()) {
+        match set.tile.tile() {
+            MahjongTile::Dragon(Dragon::White) => found.push(Yaku::Haku),
+            MahjongTile::Dragon(Dragon::Green) => found.push(Yaku::Hatsu),
+            MahjongTile::Dragon(Dragon::Red) => found.push(Yaku::Chun),
+            MahjongTile::Wind(wind) => {
+                if wind == context.seat_wind {
+                    found.push(Yaku::SeatWind);
+                }
+                if wind == context.round_wind {
+                    found.push(Yaku::RoundWind);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let chows: Vec<TileIndex> = shape.sets.iter().filter(|s| !s.is_triplet()).map(|s| s.tile).collect();
+    let triplets: Vec<&Set> = shape.sets.iter().filter(|s| s.is_triplet()).collect();
+
+    if closed && chows.len() == 4 && wait.kind == WaitKind::Ryanmen
+        && shape.pair.is_some_and(|pair| !is_yakuhai(pair, context)) {
+        found.push(Yaku::Pinfu);
+    }
+    if closed {
+        let mut sorted = chows.clone();
+        sorted.sort();
+        let mut peikou = 0;
+        let mut i = 0;
+        while i + 1 < sorted.len() {
+            if sorted[i] == sorted[i + 1] {
+                peikou += 1;
+                i += 2;
+            } else {
+                i += 1;
+            }
+        }
+        match peikou {
+            1 => found.push(Yaku::Iipeikou),
+            2 => found.push(Yaku::Ryanpeikou),
+            _ => (),
+        }
+    }
+
+    // Every set and the pair holding a terminal or honour
+    let outside = !shape.sets.is_empty()
+        && shape.sets.iter().all(|s| s.has_terminal_or_honour())
+        && shape.pair.is_some_and(|pair| pair.is_terminal_or_honour());
+    let honours = shape.tiles.iter().any(|t| t.is_honour());
+    if outside && !chows.is_empty() {
+        found.push(if honours { Yaku::Chanta } else { Yaku::Junchan });
+    }
+    if shape.tiles.iter().all(|t| t.is_terminal_or_honour()) {
+        found.push(Yaku::Honroutou);
+    }
+
+    for value in 1..=7 {
+        if [Suit::Man, Suit::Pin, Suit::Sou].iter().all(|suit| chows.iter().any(|c| c.suit() == Some(*suit) && c.value() == Some(value))) {
+            found.push(Yaku::SanshokuDoujun);
+            break;
+        }
+    }
+    for suit in [Suit::Man, Suit::Pin, Suit::Sou] {
+        if [1, 4, 7].iter().all(|value| chows.iter().any(|c| c.suit() == Some(suit) && c.value() == Some(*value))) {
+            found.push(Yaku::Ittsu);
+        }
+    }
+
+    if triplets.len() == 4 {
+        found.push(Yaku::Toitoi);
+    }
+    if triplets.iter().filter(|s| s.concealed).count() == 3 {
+        found.push(Yaku::Sanankou);
+    }
+    for value in 1..=9 {
+        if [Suit::Man, Suit::Pin, Suit::Sou].iter().all(|suit| triplets.iter().any(|t| t.tile.suit() == Some(*suit) && t.tile.value() == Some(value))) {
+            found.push(Yaku::SanshokuDoukou);
+        }
+    }
+    if triplets.iter().filter(|s| s.kind == SetKind::Kong).count() == 3 {
+        found.push(Yaku::Sankantsu);
+    }
+    let dragon_triplets = triplets.iter().filter(|t| is_dragon(t.tile)).count();
+    if dragon_triplets == 2 && shape.pair.is_some_and(is_dragon) {
+        found.push(Yaku::Shousangen);
+    }
+
+    let suits = shape.suits();
+    if suits.len() == 1 {
+        found.push(if honours { Yaku::Honitsu } else { Yaku::Chinitsu });
+    }
+    found
+}
+
+fn yakuman(shape: &Shape, wait: &Wait, context: &WinContext) -> Vec<Yaku> {
+    let mut found = Vec::new();
+    if context.first_draw && context.tsumo {
+        found.push(if context.is_dealer() { Yaku::Tenhou } else { Yaku::Chiihou });
+    }
+    if let Decomposition::ThirteenOrphans { pair } = wait.decomposition {
+        // Thirteen different orphans before the win, waiting on all of them
+        found.push(if pair == wait.tile { Yaku::KokushiJuusanmen } else { Yaku::KokushiMusou });
+        return found;
+    }
+
+    let triplets: Vec<&Set> = shape.sets.iter().filter(|s| s.is_triplet()).collect();
+    if triplets.iter().filter(|s| s.concealed).count() == 4 {
+        found.push(if wait.kind == WaitKind::Tanki { Yaku::SuuankouTanki } else { Yaku::Suuankou });
+    }
+    if triplets.iter().filter(|t| is_dragon(t.tile)).count() == 3 {
+        found.push(Yaku::Daisangen);
+    }
+    match triplets.iter().filter(|t| is_wind(t.tile)).count() {
+        4 => found.push(Yaku::Daisuushii),
+        3 if shape.pair.is_some_and(is_wind) => found.push(Yaku::Shousuushii),
+        _ => (),
+    }
+    if shape.tiles.iter().all(|t| t.is_honour()) {
+        found.push(Yaku::Tsuuiisou);
+    }
+    if shape.tiles.iter().all(|t| t.is_terminal()) {
+        found.push(Yaku::Chinroutou);
+    }
+    if shape.tiles.iter().all(|t| is_green(*t)) {
+        found.push(Yaku::Ryuuiisou);
+    }
+    if triplets.iter().filter(|s| s.kind == SetKind::Kong).count() == 4 {
+        found.push(Yaku::Suukantsu);
+    }
+    if let Some(chuuren) = chuuren(shape, wait) {
+        found.push(chuuren);
+    }
+    found
+}
+
+// Nine gates: 1112345678999 in one suit plus any tile of it, all in hand.
+// It's pure when those thirteen were held and the winning tile is the extra.
+fn chuuren(shape: &Shape, wait: &Wait) -> Option<Yaku> {
+    let called = match &wait.decomposition {
+        Decomposition::Standard { called, .. } => called,
+        _ => return None,
+    };
+    if !called.is_empty() || shape.suits().len() != 1 || shape.tiles.iter().any(|t| t.is_honour()) {
+        return None;
+    }
+    let mut counts = [0u8; 9];
+    shape.tiles.iter().for_each(|t| counts[t.value().unwrap() as usize - 1] += 1);
+    let base = [3, 1, 1, 1, 1, 1, 1, 1, 3];
+    if counts.iter().zip(base.iter()).any(|(count, needed)| count < needed) {
+        return None;
+    }
+    counts[TileIndex::try_from(wait.tile).ok()?.value()? as usize - 1] -= 1;
+    Some(if counts == base { Yaku::JunseiChuuren } else { Yaku::ChuurenPoutou })
+}
+
+fn is_dragon(tile: TileIndex) -> bool {
+    matches!(tile.tile(), MahjongTile::Dragon(_))
+}
+
+fn is_wind(tile: TileIndex) -> bool {
+    matches!(tile.tile(), MahjongTile::Wind(_))
+}
+
+// A pair of these costs pinfu
+pub(super) fn is_yakuhai(tile: TileIndex, context: &WinContext) -> bool {
+    match tile.tile() {
+        MahjongTile::Dragon(_) => true,
+        MahjongTile::Wind(wind) => wind == context.seat_wind || wind == context.round_wind,
+        _ => false,
+    }
+}
+
+// 2, 3, 4, 6 and 8 sou and the green dragon
+fn is_green(tile: TileIndex) -> bool {
+    match tile.tile() {
+        MahjongTile::Dragon(Dragon::Green) => true,
+        MahjongTile::Suit(suited) => suited.suit == Suit::Sou && matches!(suited.value, 2 | 3 | 4 | 6 | 8),
+        _ => false,
+    }
+}
+
+// Melds and decompositions are only ever made of indexed tiles
+fn index(tile: MahjongTile) -> TileIndex {
+    TileIndex::try_from(tile).expect("flowers never make up a hand")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SetKind {
+    Chow,
+    Pung,
+    Kong,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Set {
+    pub(crate) kind: SetKind,
+    // The lowest tile
+    pub(crate) tile: TileIndex,
+    // Made without help from another player's discard
+    pub(crate) concealed: bool,
+}
+
+impl Set {
+    pub(crate) fn new(meld: &Meld, concealed: bool) -> Self {
+        let kind = match meld {
+            Meld::Chow(_) => SetKind::Chow,
+            Meld::Pung(_) => SetKind::Pung,
+            Meld::Kong(_) => SetKind::Kong,
+        };
+        let tile = meld.tiles().iter().map(|t| index(*t)).min().unwrap();
+        Set { kind, tile, concealed }
+    }
+
+    pub(crate) fn is_triplet(&self) -> bool {
+        self.kind != SetKind::Chow
+    }
+
+    pub(crate) fn has_terminal_or_honour(&self) -> bool {
+        match self.kind {
+            SetKind::Chow => matches!(self.tile.value(), Some(1) | Some(7)),
+            _ => self.tile.is_terminal_or_honour(),
+        }
+    }
+
+    pub(crate) fn tiles(&self) -> Vec<TileIndex> {
+        match self.kind {
+            SetKind::Chow => (0..3).filter_map(|i| self.tile.offset(i)).collect(),
+            SetKind::Pung => vec![self.tile; 3],
+            SetKind::Kong => vec![self.tile; 4],
+        }
+    }
+}
+
+// A reading flattened into sets, with every tile of the hand
+pub(crate) struct Shape {
+    pub(crate) sets: Vec<Set>,
+    pub(crate) pair: Option<TileIndex>,
+    pub(crate) tiles: Vec<TileIndex>,
+}
+
+impl Shape {
+    pub(crate) fn new(wait: &Wait, context: &WinContext) -> Self {
+        match &wait.decomposition {
+            Decomposition::Standard { pair, concealed, called } => {
+                // A pung finished off a discard counts as open
+                let mut ron_pung = !context.tsumo && wait.kind == WaitKind::Shanpon;
+                let mut sets = Vec::new();
+                for meld in concealed {
+                    let robbed = ron_pung && matches!(meld, Meld::Pung(_)) && meld.tiles()[0] == wait.tile;
+                    ron_pung &= !robbed;
+                    sets.push(Set::new(meld, !robbed));
+                }
+                sets.extend(called.iter().map(|meld| Set::new(meld, !meld.is_open())));
+                let pair = index(*pair);
+                let mut tiles: Vec<TileIndex> = sets.iter().flat_map(|s| s.tiles()).collect();
+                tiles.extend([pair, pair]);
+                Shape { sets, pair: Some(pair), tiles }
+            }
+            Decomposition::SevenPairs(pairs) => Shape {
+                sets: Vec::new(),
+                pair: None,
+                tiles: pairs.iter().flat_map(|p| [index(*p); 2]).collect(),
+            },
+            Decomposition::ThirteenOrphans { pair } => {
+                let mut tiles: Vec<TileIndex> = TileIndex::all().filter(|t| t.is_terminal_or_honour()).collect();
+                tiles.push(index(*pair));
+                Shape { sets: Vec::new(), pair: Some(index(*pair)), tiles }
+            }
+        }
+    }
+
+    pub(crate) fn suits(&self) -> Vec<Suit> {
+        let mut suits: Vec<Suit> = Vec::new();
+        for suit in self.tiles.iter().filter_map(|t| t.suit()) {
+            if !suits.contains(&suit) {
+                suits.push(suit);
+            }
+        }
+        suits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mahjong::tile::{enums::Wind, notation::parse_hand};
+
+    use super::*;
+
+    // `hand` holds all fourteen tiles, the winning one included
+    fn yaku(hand: &str, tile: &str, context: WinContext) -> Vec<Yaku> {
+        let hand = parse_hand(hand).unwrap();
+        best_yaku(&HandCounts::from_tiles(&hand.tiles), &hand.melds, tile.parse().unwrap(), &context)
+    }
+
+    fn ron() -> WinContext {
+        WinContext::new(Wind::South, Wind::East)
+    }
+
+    fn tsumo() -> WinContext {
+        WinContext { tsumo: true, ..ron() }
+    }
+
+    #[test]
+    fn no_yaku() {
+        assert_eq!(yaku("123m456p789s11z555s", "5s", ron()), vec![]);
+        assert_eq!(yaku("123m456p11z555s[789s]", "5s", tsumo()), vec![]);
+    }
+
+    #[test]
+    fn riichi_pinfu_tsumo() {
+        let context = WinContext { riichi: true, ippatsu: true, ..tsumo() };
+        assert_eq!(
+            yaku("234m456p678s22p567m", "7m", context),
+            vec![Yaku::Riichi, Yaku::Ippatsu, Yaku::MenzenTsumo, Yaku::Tanyao, Yaku::Pinfu],
+        );
+        // A kanchan finish isn't pinfu, and neither is a pair of the seat wind
+        assert_eq!(yaku("234m456p678s22p567m", "6m", ron()), vec![Yaku::Tanyao]);
+        assert_eq!(yaku("234m456p678s22z567m", "7m", WinContext { riichi: true, ..ron() }), vec![Yaku::Riichi]);
+    }
+
+    #[test]
+    fn open_tanyao_and_yakuhai() {
+        assert_eq!(yaku("234m456p88s[678s][555p]", "8s", ron()), vec![Yaku::Tanyao]);
+        assert_eq!(yaku("234m456p88s[777z][555p]", "8s", ron()), vec![Yaku::Chun]);
+        assert_eq!(yaku("234m456p88s[222z][555p]", "8s", ron()), vec![Yaku::SeatWind]);
+        // East in the East round counts twice
+        let east = WinContext::new(Wind::East, Wind::East);
+        assert_eq!(yaku("234m456p88s[111z][555p]", "8s", east), vec![Yaku::SeatWind, Yaku::RoundWind]);
+    }
+
+    #[test]
+    fn peikou() {
+        assert_eq!(yaku("223344m456p678s99p", "4m", ron()), vec![Yaku::Pinfu, Yaku::Iipeikou]);
+        // Read as two pairs of runs rather than seven pairs
+        assert_eq!(yaku("223344m667788s99p", "9p", ron()), vec![Yaku::Ryanpeikou]);
+        assert_eq!(yaku("223344m456p99p[234m]", "9p", ron()), vec![]);
+    }
+
+    #[test]
+    fn outside_hands() {
+        assert_eq!(yaku("123m789p11z999s[789s]", "1z", ron()), vec![Yaku::Chanta]);
+        assert_eq!(total_han(&[Yaku::Chanta], false), 1);
+        assert_eq!(yaku("123m789p11s999m789s", "1s", ron()), vec![Yaku::Junchan]);
+        assert_eq!(yaku("111m999p11z[999s][777z]", "1z", ron()), vec![Yaku::Chun, Yaku::Honroutou, Yaku::Toitoi]);
+    }
+
+    #[test]
+    fn runs_across_suits() {
+        assert_eq!(yaku("123m123p123s55z789m", "5z", ron()), vec![Yaku::Chanta, Yaku::SanshokuDoujun]);
+        assert_eq!(yaku("123456789p55z789m", "5z", ron()), vec![Yaku::Ittsu]);
+        assert_eq!(yaku("123456789p99m[789m]", "9m", ron()), vec![Yaku::Ittsu]);
+        assert_eq!(total_han(&[Yaku::Ittsu], false), 1);
+    }
+
+    #[test]
+    fn triplets() {
+        // Ron on a shanpon leaves the pung it finished open
+        assert_eq!(yaku("222m333p444s55z666s", "6s", ron()), vec![Yaku::Toitoi, Yaku::Sanankou]);
+        assert_eq!(yaku("222m333p444s55s666s", "6s", ron()), vec![Yaku::Tanyao, Yaku::Toitoi, Yaku::Sanankou]);
+        assert_eq!(yaku("222m222p55s[222s][888s]", "5s", ron()), vec![Yaku::Tanyao, Yaku::Toitoi, Yaku::SanshokuDoukou]);
+        assert_eq!(yaku("234m55p(1111z)(2222p)[3333s]", "5p", ron()), vec![Yaku::RoundWind, Yaku::Sankantsu]);
+        assert_eq!(yaku("123m55z666z777z[999s]", "6z", ron()), vec![Yaku::Hatsu, Yaku::Chun, Yaku::Chanta, Yaku::Shousangen]);
+    }
+
+    #[test]
+    fn flushes_and_pairs() {
+        assert_eq!(yaku("1122m3355p6677s11z", "1z", ron()), vec![Yaku::Chiitoitsu]);
+        assert_eq!(yaku("123456m789m11z555z", "5z", ron()), vec![Yaku::Haku, Yaku::Ittsu, Yaku::Honitsu]);
+        assert_eq!(yaku("123456789m11m789m", "1m", ron()), vec![Yaku::Pinfu, Yaku::Iipeikou, Yaku::Ittsu, Yaku::Chinitsu]);
+        assert_eq!(total_han(&[Yaku::Chinitsu], false), 5);
+    }
+
+    #[test]
+    fn situational() {
+        let hand = "123m456p789s11z555s";
+        assert_eq!(yaku(hand, "5s", WinContext { last_tile: true, ..tsumo() }), vec![Yaku::MenzenTsumo, Yaku::Haitei]);
+        assert_eq!(yaku(hand, "5s", WinContext { last_tile: true, ..ron() }), vec![Yaku::Houtei]);
+        assert_eq!(yaku(hand, "5s", WinContext { chankan: true, ..ron() }), vec![Yaku::Chankan]);
+        assert_eq!(yaku(hand, "5s", WinContext { double_riichi: true, ..ron() }), vec![Yaku::DoubleRiichi]);
+        let open = "123m456p11z555s[789s]";
+        assert_eq!(yaku(open, "5s", WinContext { rinshan: true, last_tile: true, ..tsumo() }), vec![Yaku::Rinshan]);
+    }
+
+    #[test]
+    fn yakuman() {
+        assert_eq!(yaku("19m19p19s1234567z1m", "1m", ron()), vec![Yaku::KokushiJuusanmen]);
+        assert_eq!(yaku("19m19p19s1234567z1m", "9m", ron()), vec![Yaku::KokushiMusou]);
+        assert_eq!(yaku("111m222p333s44z555z", "4z", ron()), vec![Yaku::SuuankouTanki]);
+        assert_eq!(yaku("111m222p333s44z555z", "5z", tsumo()), vec![Yaku::Suuankou]);
+        assert_eq!(yaku("123m55p[555z][666z][777z]", "5p", ron()), vec![Yaku::Daisangen]);
+        assert_eq!(yaku("111222333z44z789m", "4z", ron()), vec![Yaku::Shousuushii]);
+        assert_eq!(yaku("55z[111z][222z][333z][444z]", "5z", ron()), vec![Yaku::Daisuushii, Yaku::Tsuuiisou]);
+        assert_eq!(yaku("11m[999p][111s][999s][111p]", "1m", ron()), vec![Yaku::Chinroutou]);
+        assert_eq!(yaku("234s234s666s888s66z", "6z", WinContext { riichi: true, ..ron() }), vec![Yaku::Ryuuiisou]);
+        assert_eq!(yaku("11112345678999p", "1p", ron()), vec![Yaku::JunseiChuuren]);
+        assert_eq!(yaku("11122345678999p", "2p", ron()), vec![Yaku::JunseiChuuren]);
+        assert_eq!(yaku("11122345678999p", "5p", ron()), vec![Yaku::ChuurenPoutou]);
+        assert_eq!(yaku("55p(1111z)(2222p)[3333s][4444s]", "5p", ron()), vec![Yaku::Suukantsu]);
+        let dealer = WinContext { first_draw: true, tsumo: true, ..WinContext::new(Wind::East, Wind::East) };
+        assert_eq!(yaku("123m456p789s11z555s", "5s", dealer), vec![Yaku::Tenhou]);
+        assert_eq!(Yaku::Daisuushii.han(true), 26);
+    }
+}
@@ -1,8 +1,22 @@
-mod mahjong;
-
-use mahjong::game::Game;
+use mahjong::mahjong::game_match::Match;
+use mahjong::mahjong::ruleset::ruleset::Ruleset;
 
+// Usage: mahjong [seed] [ruleset file]
 fn main() {
-    let mut game = Game::new();
-    game.run()
+    let ruleset = match std::env::args().nth(2) {
+        Some(path) => Ruleset::load(&path).unwrap_or_else(|error| panic!("Couldn't read {}: {}", path, error)),
+        None => Ruleset::tenhou(),
+    };
+    let mut game_match = match std::env::args().nth(1).and_then(|seed| seed.parse().ok()) {
+        Some(seed) => Match::with_seed(ruleset, seed),
+        None => Match::new(ruleset),
+    };
+    println!("Seed {}", game_match.seed());
+    while !game_match.is_over() {
+        println!("{:?} {}, {} honba", game_match.round(), game_match.dealer() + 1, game_match.honba());
+        game_match.play_hand();
+    }
+    for (player, points) in game_match.points().iter().enumerate() {
+        println!("Player {}: {}", player + 1, points);
+    }
 }